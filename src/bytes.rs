@@ -0,0 +1,343 @@
+use {Style, Sign, Float};
+use ramp::Int;
+
+use std::i64;
+
+// Conversions between non-negative integers and their little-endian
+// bytes, going via hexadecimal so that they take linear time.
+
+// The bytes of `n`, least significant first, with no trailing zeros
+// (so zero has none).
+pub fn int_to_le_bytes(n: &Int) -> Vec<u8> {
+    debug_assert!(n.sign() >= 0);
+    if *n == 0 {
+        return vec![]
+    }
+    let hex = n.to_str_radix(16, false);
+    let hex = hex.as_bytes();
+    let mut bytes = Vec::with_capacity((hex.len() + 1) / 2);
+    let mut end = hex.len();
+    while end > 0 {
+        let start = end.saturating_sub(2);
+        let byte = hex[start..end].iter()
+            .fold(0, |b, &c| b * 16 + (c as char).to_digit(16).unwrap() as u8);
+        bytes.push(byte);
+        end = start;
+    }
+    bytes
+}
+
+// The integer with little-endian bytes `bytes`.
+pub fn int_from_le_bytes(bytes: &[u8]) -> Int {
+    if bytes.is_empty() {
+        return Int::zero()
+    }
+    let mut hex = String::with_capacity(2 * bytes.len());
+    for b in bytes.iter().rev() {
+        hex.push_str(&format!("{:02x}", b));
+    }
+    Int::from_str_radix(&hex, 16).unwrap()
+}
+
+// The compact encoding is a tag byte with the style and sign, then
+// the precision as a varint, and then for normal values, the
+// exponent as a zigzag varint and the significand with its trailing
+// zeros removed, as little-endian bytes filling the rest.
+const ZERO: u8 = 0;
+const NORMAL: u8 = 1;
+const INFINITY: u8 = 2;
+const NAN: u8 = 3;
+const NEG: u8 = 4;
+
+impl Float {
+    /// A compact encoding of `self` as bytes, which can be decoded
+    /// exactly (including the precision) with `from_bytes`.
+    ///
+    /// This is a tag byte recording the sign and whether `self` is
+    /// zero, infinite, NaN or normal, followed by the precision as a
+    /// LEB128 varint. Normal values then have their exponent as a
+    /// zigzag LEB128 varint, and the significand without its
+    /// trailing zero bits, as a little-endian integer taking up the
+    /// rest of the bytes, e.g. `1.5` with any precision is `[1, p, 0,
+    /// 3]` (for `p < 128`).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.debug_assert_valid();
+        let mut tag = match self.style {
+            Style::Zero => ZERO,
+            Style::Normal => NORMAL,
+            Style::Infinity => INFINITY,
+            Style::NaN => NAN,
+        };
+        if self.sign == Sign::Neg {
+            tag |= NEG;
+        }
+        let mut bytes = vec![tag];
+        write_varint(&mut bytes, self.prec as u64);
+        if let Style::Normal = self.style {
+            write_varint(&mut bytes, zigzag(self.exp));
+            let signif = &self.signif >> self.signif.trailing_zeros() as usize;
+            bytes.extend(int_to_le_bytes(&signif));
+        }
+        bytes
+    }
+
+    /// Decode the output of `to_bytes`, returning `None` if `bytes`
+    /// isn't a valid encoding of any value.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Float> {
+        let (&tag, mut rest) = match bytes.split_first() {
+            Some(x) => x,
+            None => return None,
+        };
+        let prec = match read_varint(&mut rest) {
+            Some(p) if 0 < p && p <= u32::max_value() as u64 => p as u32,
+            _ => return None,
+        };
+        let sign = if tag & NEG != 0 { Sign::Neg } else { Sign::Pos };
+        let mut x = match tag & !NEG {
+            ZERO => Float::zero_(prec, sign),
+            INFINITY => Float::inf(prec, sign),
+            NAN => Float::nan(prec),
+            NORMAL => {
+                let exp = match read_varint(&mut rest) {
+                    Some(e) => unzigzag(e),
+                    None => return None,
+                };
+                // the significand must be odd, and have no more than
+                // `prec` bits.
+                if rest.first().map_or(true, |b| b & 1 == 0) || rest.last() == Some(&0) ||
+                    rest.len() as u64 > (prec as u64 + 7) / 8
+                {
+                    return None
+                }
+                let signif = int_from_le_bytes(rest);
+                rest = &[];
+                let bits = signif.bit_length();
+                if bits > prec || exp == i64::MIN || exp == i64::MAX {
+                    return None
+                }
+                Float {
+                    prec: prec,
+                    sign: sign,
+                    exp: exp,
+                    signif: signif << (prec - bits) as usize,
+                    style: Style::Normal,
+                }
+            }
+            _ => return None,
+        };
+        x.sign = sign;
+        if rest.is_empty() { Some(x) } else { None }
+    }
+}
+
+// The sortable encoding starts with a byte for the class of the
+// value, in numeric order, and then for normal values, the exponent
+// and the bits of the significand after the leading one, in an
+// order-preserving way (and inverted for negative values), and
+// finally the precision, which breaks ties between equal values.
+const SORT_NEG_INFINITY: u8 = 1;
+const SORT_NEG_NORMAL: u8 = 2;
+const SORT_ZERO: u8 = 3;
+const SORT_POS_NORMAL: u8 = 4;
+const SORT_POS_INFINITY: u8 = 5;
+const SORT_NAN: u8 = 6;
+
+impl Float {
+    /// An encoding of `self` as bytes whose lexicographic order is
+    /// the same as the numeric order of the values (as given by
+    /// `PartialOrd`), which can be decoded exactly with
+    /// `from_sortable_bytes`.
+    ///
+    /// Values that compare equal, such as `0` and `-0`, or the same
+    /// number with different precisions, have different encodings
+    /// that are adjacent in the order: zeros are ordered by sign and
+    /// then precision, and other values by precision. NaNs, which
+    /// have no numeric order, sort after everything else.
+    pub fn to_sortable_bytes(&self) -> Vec<u8> {
+        self.debug_assert_valid();
+        let class = match (self.style, self.sign) {
+            (Style::NaN, _) => SORT_NAN,
+            (Style::Zero, _) => SORT_ZERO,
+            (Style::Infinity, Sign::Neg) => SORT_NEG_INFINITY,
+            (Style::Infinity, Sign::Pos) => SORT_POS_INFINITY,
+            (Style::Normal, Sign::Neg) => SORT_NEG_NORMAL,
+            (Style::Normal, Sign::Pos) => SORT_POS_NORMAL,
+        };
+        let mut bytes = vec![class];
+        match self.style {
+            Style::Normal => {
+                let start = bytes.len();
+                // flipping the sign bit makes the bytes of the
+                // exponent sort correctly as unsigned.
+                let exp = self.exp as u64 ^ (1 << 63);
+                bytes.extend((0..8).rev().map(|i| (exp >> (8 * i)) as u8));
+                write_escaped(&mut bytes, &fraction_bytes(self));
+                if self.sign == Sign::Neg {
+                    for b in &mut bytes[start..] {
+                        *b = !*b;
+                    }
+                }
+            }
+            Style::Zero | Style::NaN => bytes.push((self.sign == Sign::Pos) as u8),
+            Style::Infinity => {}
+        }
+        bytes.extend((0..4).rev().map(|i| (self.prec >> (8 * i)) as u8));
+        bytes
+    }
+
+    /// Decode the output of `to_sortable_bytes`, returning `None` if
+    /// `bytes` isn't a valid encoding of any value.
+    pub fn from_sortable_bytes(bytes: &[u8]) -> Option<Float> {
+        if bytes.len() < 5 {
+            return None
+        }
+        let (body, prec) = bytes.split_at(bytes.len() - 4);
+        let prec = prec.iter().fold(0, |p, &b| p << 8 | b as u32);
+        if prec == 0 {
+            return None
+        }
+        let (class, body) = (body[0], &body[1..]);
+        let sign = if class < SORT_ZERO { Sign::Neg } else { Sign::Pos };
+        match class {
+            SORT_NEG_INFINITY | SORT_POS_INFINITY if body.is_empty() => {
+                Some(Float::inf(prec, sign))
+            }
+            SORT_ZERO | SORT_NAN if body.len() == 1 && body[0] <= 1 => {
+                let sign = if body[0] == 1 { Sign::Pos } else { Sign::Neg };
+                let mut x = if class == SORT_ZERO {
+                    Float::zero_(prec, sign)
+                } else {
+                    Float::nan(prec)
+                };
+                x.sign = sign;
+                Some(x)
+            }
+            SORT_NEG_NORMAL | SORT_POS_NORMAL if body.len() >= 10 => {
+                let mut body = body.to_vec();
+                if sign == Sign::Neg {
+                    for b in &mut body {
+                        *b = !*b;
+                    }
+                }
+                let exp = body[..8].iter().fold(0, |e, &b| e << 8 | b as u64);
+                let exp = (exp ^ (1 << 63)) as i64;
+                let fraction = match read_escaped(&body[8..]) {
+                    Some(f) => f,
+                    None => return None,
+                };
+                from_fraction_bytes(sign, exp, &fraction, prec)
+            }
+            _ => None,
+        }
+    }
+}
+
+// The bits of the significand of `x` after the leading one, without
+// trailing zeros, padded with zeros to a whole number of bytes, in
+// big-endian order.
+fn fraction_bytes(x: &Float) -> Vec<u8> {
+    let trimmed = &x.signif >> x.signif.trailing_zeros() as usize;
+    let bits = trimmed.bit_length() as usize - 1;
+    let len = (bits + 7) / 8;
+    let mut fraction = trimmed - &(Int::from(1) << bits);
+    fraction <<= 8 * len - bits;
+
+    let mut bytes = int_to_le_bytes(&fraction);
+    bytes.resize(len, 0);
+    bytes.reverse();
+    bytes
+}
+
+fn from_fraction_bytes(sign: Sign, exp: i64, fraction: &[u8], prec: u32) -> Option<Float> {
+    if fraction.last() == Some(&0) || exp == i64::MIN || exp == i64::MAX {
+        return None
+    }
+    let len = fraction.len() as u64;
+    let padding = fraction.last().map_or(0, |b| b.trailing_zeros()) as u64;
+    let bits = 8 * len - padding;
+    if bits >= prec as u64 {
+        return None
+    }
+    let le: Vec<u8> = fraction.iter().rev().cloned().collect();
+    let mut signif = (int_from_le_bytes(&le) >> padding as usize) + &(Int::from(1) << bits as usize);
+    signif <<= (prec as u64 - 1 - bits) as usize;
+    Some(Float {
+        prec: prec,
+        sign: sign,
+        exp: exp,
+        signif: signif,
+        style: Style::Normal,
+    })
+}
+
+// Append `data` so that it is followed by a terminator and nothing
+// written this way is a prefix of anything else, preserving the
+// order: zero bytes become `0, 0xff`, and the terminator is `0, 0`.
+fn write_escaped(bytes: &mut Vec<u8>, data: &[u8]) {
+    for &b in data {
+        bytes.push(b);
+        if b == 0 {
+            bytes.push(0xff);
+        }
+    }
+    bytes.push(0);
+    bytes.push(0);
+}
+
+// Read the data written by `write_escaped`, which must be all of
+// `bytes`.
+fn read_escaped(bytes: &[u8]) -> Option<Vec<u8>> {
+    let mut data = vec![];
+    let mut iter = bytes.iter();
+    while let Some(&b) = iter.next() {
+        if b != 0 {
+            data.push(b);
+            continue
+        }
+        match iter.next() {
+            Some(&0xff) => data.push(0),
+            Some(&0) => return if iter.next().is_none() { Some(data) } else { None },
+            _ => return None,
+        }
+    }
+    None
+}
+
+// Append `n` as an unsigned LEB128 varint: seven bits at a time,
+// least significant first, with the high bit set on all but the last
+// byte.
+fn write_varint(bytes: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        bytes.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+}
+
+// Read a varint written by `write_varint` from the front of `bytes`,
+// rejecting ones that are too long or have redundant bytes.
+fn read_varint(bytes: &mut &[u8]) -> Option<u64> {
+    let mut n = 0u64;
+    for (i, &b) in bytes.iter().enumerate() {
+        let shift = 7 * i as u32;
+        if shift >= 64 || (shift == 63 && b > 1) || (i > 0 && b == 0) {
+            return None
+        }
+        n |= ((b & 0x7f) as u64) << shift;
+        if b & 0x80 == 0 {
+            *bytes = &bytes[i + 1..];
+            return Some(n)
+        }
+    }
+    None
+}
+
+// Map signed integers to unsigned ones with small magnitudes staying
+// small: 0, -1, 1, -2, ... become 0, 1, 2, 3, ...
+fn zigzag(n: i64) -> u64 {
+    ((n << 1) ^ (n >> 63)) as u64
+}
+
+fn unzigzag(n: u64) -> i64 {
+    (n >> 1) as i64 ^ -((n & 1) as i64)
+}
@@ -0,0 +1,213 @@
+use Float;
+
+use std::cell::RefCell;
+use std::i64;
+use std::fmt;
+use std::rc::Rc;
+
+/// An IEEE 754 exception, signalled when an operation produces a
+/// result that is in some way suspect.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Exception {
+    /// The result was rounded, i.e. it is not exactly the true
+    /// value.
+    Inexact,
+    /// The true result was too large to represent, and was rounded
+    /// to an infinity or the largest finite value.
+    Overflow,
+    /// The true result was non-zero and too small to represent
+    /// exactly.
+    Underflow,
+    /// The operation has no meaningful result, and so produced NaN,
+    /// e.g. `0/0`, `inf - inf` or the square root of a negative
+    /// number.
+    Invalid,
+    /// An exact infinite result was produced from finite operands,
+    /// i.e. a non-zero number divided by zero.
+    DivideByZero,
+}
+
+const EXCEPTIONS: [Exception; 5] = [Exception::Inexact, Exception::Overflow,
+                                    Exception::Underflow, Exception::Invalid,
+                                    Exception::DivideByZero];
+
+/// A set of `Exception`s.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct Flags {
+    bits: u8,
+}
+
+impl Flags {
+    /// The set containing no exceptions.
+    pub fn empty() -> Flags {
+        Flags { bits: 0 }
+    }
+    pub fn is_empty(&self) -> bool {
+        self.bits == 0
+    }
+    pub fn contains(&self, e: Exception) -> bool {
+        self.bits & bit(e) != 0
+    }
+    pub fn insert(&mut self, e: Exception) {
+        self.bits |= bit(e)
+    }
+    pub fn remove(&mut self, e: Exception) {
+        self.bits &= !bit(e)
+    }
+}
+
+impl fmt::Debug for Flags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_set()
+         .entries(EXCEPTIONS.iter().filter(|&&e| self.contains(e)))
+         .finish()
+    }
+}
+
+fn bit(e: Exception) -> u8 {
+    1 << e as u8
+}
+
+struct Env {
+    flags: Flags,
+    trap: Option<Rc<Box<Fn(Exception)>>>,
+    // whether the trap is currently running
+    trapping: bool,
+    emin: i64,
+    emax: i64,
+}
+
+const EMIN: i64 = i64::MIN + 1;
+const EMAX: i64 = i64::MAX - 1;
+
+thread_local!(static ENV: RefCell<Env> = RefCell::new(Env {
+    flags: Flags::empty(),
+    trap: None,
+    trapping: false,
+    emin: EMIN,
+    emax: EMAX,
+}));
+
+impl Float {
+    /// The exceptions that have been raised by operations on this
+    /// thread since the flags were last cleared.
+    pub fn flags() -> Flags {
+        ENV.with(|env| env.borrow().flags)
+    }
+    /// Reset the exception flags for this thread.
+    pub fn clear_flags() {
+        ENV.with(|env| env.borrow_mut().flags = Flags::empty())
+    }
+    /// Call `trap` whenever an operation on this thread raises an
+    /// exception, after the corresponding flag has been set. Any
+    /// exceptions raised by operations inside `trap` itself set their
+    /// flags, but do not call `trap` again.
+    pub fn set_trap<F: Fn(Exception) + 'static>(trap: F) {
+        ENV.with(|env| env.borrow_mut().trap = Some(Rc::new(Box::new(trap))))
+    }
+    /// Stop calling the function registered with `set_trap`.
+    pub fn clear_trap() {
+        ENV.with(|env| env.borrow_mut().trap = None)
+    }
+
+    /// Restrict the results of operations on this thread to have
+    /// exponents between `emin` and `emax` (inclusive), where a
+    /// number with exponent `e` lies in `[2**e, 2**(e + 1))`.
+    ///
+    /// Results that are too large overflow to infinity (or the
+    /// largest finite value, depending on the rounding mode), and
+    /// results smaller than `2**emin` are rounded to subnormal
+    /// values with fewer than the full precision. For example, a
+    /// precision of 53 with `emin == -1022` and `emax == 1023`
+    /// behaves exactly like `f64`.
+    ///
+    /// Values that are already outside the range are not changed,
+    /// but can be brought into it with `with_precision_round`.
+    pub fn set_exp_range(emin: i64, emax: i64) {
+        assert!(EMIN <= emin && emin <= emax && emax <= EMAX,
+                "invalid exponent range {}..{}", emin, emax);
+        ENV.with(|env| {
+            let mut env = env.borrow_mut();
+            env.emin = emin;
+            env.emax = emax;
+        })
+    }
+    /// The smallest and largest exponents of results of operations on
+    /// this thread.
+    pub fn exp_range() -> (i64, i64) {
+        ENV.with(|env| {
+            let env = env.borrow();
+            (env.emin, env.emax)
+        })
+    }
+    /// Reset the exponent range for this thread to the default,
+    /// which is as large as possible, and so has no subnormals.
+    pub fn reset_exp_range() {
+        Float::set_exp_range(EMIN, EMAX)
+    }
+}
+
+// record that `e` has occurred, calling the trap (if any).
+pub fn raise(e: Exception) {
+    let trap = ENV.with(|env| {
+        let mut env = env.borrow_mut();
+        env.flags.insert(e);
+        if env.trapping {
+            None
+        } else {
+            env.trap.clone()
+        }
+    });
+    if let Some(trap) = trap {
+        // reset even if `trap` panics, so that later exceptions still
+        // call it.
+        struct Reset;
+        impl Drop for Reset {
+            fn drop(&mut self) {
+                ENV.with(|env| env.borrow_mut().trapping = false)
+            }
+        }
+
+        ENV.with(|env| env.borrow_mut().trapping = true);
+        let _reset = Reset;
+        trap(e);
+    }
+}
+
+// Run `f` with the default exponent range, without calling the trap
+// or changing the flags, for computations that are only an internal
+// detail of some other operation.
+pub fn quietly<T, F: FnOnce() -> T>(f: F) -> T {
+    struct Restore {
+        flags: Flags,
+        emin: i64,
+        emax: i64,
+        trapping: bool,
+    }
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            ENV.with(|env| {
+                let mut env = env.borrow_mut();
+                env.flags = self.flags;
+                env.emin = self.emin;
+                env.emax = self.emax;
+                env.trapping = self.trapping;
+            })
+        }
+    }
+
+    let _restore = ENV.with(|env| {
+        let mut env = env.borrow_mut();
+        let saved = Restore {
+            flags: env.flags,
+            emin: env.emin,
+            emax: env.emax,
+            trapping: env.trapping,
+        };
+        env.emin = EMIN;
+        env.emax = EMAX;
+        env.trapping = true;
+        saved
+    });
+    f()
+}
@@ -0,0 +1,810 @@
+use {Style, Sign, Float, RoundingMode};
+use {env, rounding};
+use parse::from_scaled;
+use ramp::Int;
+
+use std::{cmp, fmt, iter};
+
+// Converting to a string of digits in some radix reduces to computing
+// `|x| * radix**-s` rounded to an integer, for a scale `s` that puts
+// the digits we want in the integer part. This is done exactly when
+// the numbers involved are small enough, and otherwise with interval
+// arithmetic in increasing precisions until both ends of the interval
+// round to the same integer.
+
+// How many more bits than the size of the input and output the exact
+// computation is allowed to use before switching to the approximate
+// one, both here and in parsing (`parse::from_scaled`). The
+// approximate one can only fail to terminate if the scaled value lies
+// exactly on a rounding boundary, which requires the exact
+// computation to be at most twice that size.
+pub const EXACT_SLACK: f64 = 16384.0;
+
+// the number of fractional bits in the result of `log2_fixed`.
+const LOG_BITS: usize = 128;
+
+// log2(radix), as a fixed point number with `LOG_BITS` fractional
+// bits.
+fn log2_fixed(radix: u32) -> Int {
+    const WORKING: usize = LOG_BITS + 64;
+    let int_part = 31 - radix.leading_zeros();
+    let two = Int::from(2) << WORKING;
+
+    // compute the bits of log2(y) for y = radix / 2**int_part, in
+    // [1, 2), by repeated squaring.
+    let mut y = Int::from(radix) << (WORKING - int_part as usize);
+    let mut log = Int::from(int_part);
+    for _ in 0..LOG_BITS {
+        y = (&y * &y) >> WORKING;
+        log <<= 1;
+        if y >= two {
+            y >>= 1;
+            log += 1;
+        }
+    }
+    log
+}
+
+// floor(exp / log2(radix)), given `log2_fixed(radix)`.
+fn floor_div_log(exp: i64, log: &Int) -> i64 {
+    let (mut q, r) = (Int::from(exp) << LOG_BITS).divmod(log);
+    if r.sign() < 0 {
+        q -= 1;
+    }
+    i64::from(&q)
+}
+
+// round `y`, which is normal and positive, to an integer, as if it
+// had sign `sign`.
+fn round_to_int(y: &Float, sign: Sign, mode: RoundingMode) -> Int {
+    let prec = y.prec as i64;
+    let shift = y.exp.saturating_sub(prec - 1);
+    if shift >= 0 {
+        return &y.signif << shift as usize
+    }
+    let (mut q, half, sticky) = if shift < -prec {
+        (Int::zero(), false, true)
+    } else {
+        let n = -shift as u32;
+        (&y.signif >> n as usize, y.signif.bit(n - 1), y.signif.trailing_zeros() < n - 1)
+    };
+    if rounding::round_away(mode, sign, q.bit(0), half, sticky) {
+        q += 1;
+    }
+    q
+}
+
+// `base**n`, rounded down and up to at least `prec` bits.
+pub fn pow_bounds(base: u32, n: u64, prec: u32) -> (Float, Float) {
+    // each squaring or multiplication can add to the relative error,
+    // so work with a few more bits.
+    let bits = 64 - n.leading_zeros();
+    let work = prec + 2 * bits + 8;
+    let base = Float::from(base);
+    let mut lo = Float::from(1).with_precision(work);
+    let mut hi = lo.clone();
+    for i in (0..bits).rev() {
+        lo = Float::mul_to(&lo, &lo, work, RoundingMode::TowardNegative);
+        hi = Float::mul_to(&hi, &hi, work, RoundingMode::TowardPositive);
+        if n & (1 << i) != 0 {
+            lo = Float::mul_to(&lo, &base, work, RoundingMode::TowardNegative);
+            hi = Float::mul_to(&hi, &base, work, RoundingMode::TowardPositive);
+        }
+    }
+    (lo, hi)
+}
+
+// `x * base**n`, for a positive `x`, rounded down and up to `prec`
+// bits. The power is applied in two halves, as it can be beyond the
+// range of exponents when `x` is near the other end of it.
+pub fn scale_bounds(x: &Float, base: u32, n: i64, prec: u32) -> (Float, Float) {
+    let (down, up) = (RoundingMode::TowardNegative, RoundingMode::TowardPositive);
+    let m = n.wrapping_abs() as u64;
+    let (lo1, hi1) = pow_bounds(base, m / 2, prec);
+    let (lo2, hi2) = pow_bounds(base, m - m / 2, prec);
+    if n >= 0 {
+        (Float::mul_to(&Float::mul_to(x, &lo1, prec, down), &lo2, prec, down),
+         Float::mul_to(&Float::mul_to(x, &hi1, prec, up), &hi2, prec, up))
+    } else {
+        (Float::div_to(&Float::div_to(x, &hi1, prec, down), &hi2, prec, down),
+         Float::div_to(&Float::div_to(x, &lo1, prec, up), &lo2, prec, up))
+    }
+}
+
+// `|x| * radix**-s` rounded to an integer according to `mode` (and
+// the sign of `x`), for a normal `x`.
+fn round_scaled(x: &Float, radix: u32, s: i64, mode: RoundingMode) -> Int {
+    // radix == 2**twos * odd
+    let twos = radix.trailing_zeros();
+    let odd = radix >> twos;
+
+    // |x| * radix**-s == signif * 2**shift * odd**-s
+    let shift = x.exp as f64 - (x.prec as f64 - 1.0) - s as f64 * twos as f64;
+    let out_bits = (x.exp as f64 + 1.0 - s as f64 * (radix as f64).log2()).max(0.0);
+    let cost = (s as f64).abs() * (odd as f64).log2() + shift.abs();
+
+    if cost <= 2.0 * (x.prec as f64 + out_bits) + EXACT_SLACK {
+        // the true shift is small, so any overflow in the
+        // intermediate steps cancels out.
+        let shift = x.exp.wrapping_sub(x.prec as i64 - 1).wrapping_sub(s * twos as i64);
+        exact_round_scaled(x, odd, s, shift, mode)
+    } else {
+        approx_round_scaled(x, twos, odd, s, out_bits as u32, mode)
+    }
+}
+
+fn exact_round_scaled(x: &Float, odd: u32, s: i64, shift: i64, mode: RoundingMode) -> Int {
+    let mut num = x.signif.clone();
+    let mut den = Int::from(1);
+    if odd == 1 {
+        // nothing to do, however large `s` is.
+    } else if s < 0 {
+        num *= &Int::from(odd).pow(-s as usize);
+    } else if s > 0 {
+        den = Int::from(odd).pow(s as usize);
+    }
+    if shift >= 0 {
+        num <<= shift as usize;
+    } else {
+        den <<= -shift as usize;
+    }
+
+    let (mut q, r) = num.divmod(&den);
+    let twice = &r << 1;
+    let half = twice >= den;
+    let sticky = if half { twice != den } else { r != 0 };
+    if rounding::round_away(mode, x.sign, q.bit(0), half, sticky) {
+        q += 1;
+    }
+    q
+}
+
+fn approx_round_scaled(x: &Float, twos: u32, odd: u32, s: i64, out_bits: u32,
+                       mode: RoundingMode) -> Int {
+    let abs = x.clone().abs();
+    let mut prec = out_bits + 64;
+    loop {
+        let (lo, hi) = scale_bounds(&abs, odd, -s, prec);
+        let lo = round_to_int(&lo.mul_exp2(-s * twos as i64), x.sign, mode);
+        let hi = round_to_int(&hi.mul_exp2(-s * twos as i64), x.sign, mode);
+        if lo == hi {
+            return lo
+        }
+        prec *= 2;
+    }
+}
+
+// `|x|` rounded to `digits` significant digits in base `radix`,
+// according to `mode` (and the sign of `x`), for a normal `x`. This
+// returns those digits as an integer, along with the exponent of the
+// leading one.
+pub fn to_digits(x: &Float, radix: u32, digits: u32, mode: RoundingMode) -> (Int, i64) {
+    debug_assert!(digits > 0);
+    if radix.is_power_of_two() {
+        // only the exponent modulo `log2(radix)` affects the digits,
+        // so work with a small one, keeping the scales below away
+        // from the limits of `i64` even for the most extreme `x`.
+        let twos = radix.trailing_zeros() as i64;
+        let mut q = x.exp / twos;
+        if x.exp % twos < 0 {
+            q -= 1;
+        }
+        if q != 0 {
+            let mut y = x.clone();
+            y.exp -= q * twos;
+            let (n, k) = to_digits(&y, radix, digits, mode);
+            return (n, k + q)
+        }
+    }
+    env::quietly(|| {
+        let lo = Int::from(radix).pow(digits as usize - 1);
+        let hi = &lo * &Int::from(radix);
+
+        // the leading digit has exponent either this or one more.
+        let mut k = floor_div_log(x.exp, &log2_fixed(radix));
+        loop {
+            let n = round_scaled(x, radix, k - (digits as i64 - 1), mode);
+            if n >= hi {
+                k += 1;
+            } else if n < lo {
+                k -= 1;
+            } else {
+                return (n, k)
+            }
+        }
+    })
+}
+
+// `|x|` rounded to `frac` digits after the point in base `radix`, as
+// an integer (i.e. scaled by `radix**frac`), for a normal `x`.
+pub fn to_fixed(x: &Float, radix: u32, frac: u32, mode: RoundingMode) -> Int {
+    env::quietly(|| round_scaled(x, radix, -(frac as i64), mode))
+}
+
+// the number of significant digits in base `radix` that is always
+// enough to distinguish values with precision `prec`.
+pub fn round_trip_digits(prec: u32, radix: u32) -> u32 {
+    (prec as f64 / (radix as f64).log2()).ceil() as u32 + 1
+}
+
+// the number of significant digits MPFR's `mpfr_get_str_ndigits`
+// uses for precision `prec` in base `radix`: like
+// `round_trip_digits`, but exact in bases that are powers of two.
+fn mpfr_digits(prec: u32, radix: u32) -> u32 {
+    if radix.is_power_of_two() {
+        let k = radix.trailing_zeros();
+        (prec - 1 + k - 1) / k + 1
+    } else {
+        round_trip_digits(prec, radix)
+    }
+}
+
+pub fn zeros(n: usize) -> String {
+    iter::repeat('0').take(n).collect()
+}
+
+// `digits` with a point inserted before the last `frac` of them.
+fn fixed_point(mut digits: String, frac: usize) -> String {
+    if digits.len() <= frac {
+        digits = zeros(frac + 1 - digits.len()) + &digits;
+    }
+    if frac > 0 {
+        let point = digits.len() - frac;
+        digits.insert(point, '.');
+    }
+    digits
+}
+
+// the digits `digits` with the leading one having exponent `exp`,
+// written without an exponent.
+fn positional(digits: &str, exp: i64) -> String {
+    if exp < 0 {
+        format!("0.{}{}", zeros(-(exp + 1) as usize), digits)
+    } else {
+        let int_digits = exp as usize + 1;
+        if digits.len() <= int_digits {
+            format!("{}{}", digits, zeros(int_digits - digits.len()))
+        } else {
+            format!("{}.{}", &digits[..int_digits], &digits[int_digits..])
+        }
+    }
+}
+
+// the digits `digits` with the leading one having exponent `exp`,
+// written as `d.ddd` followed by the exponent marker and `exp`.
+pub fn scientific(digits: &str, exp: i64, marker: &str) -> String {
+    if digits.len() == 1 {
+        format!("{}{}{}", digits, marker, exp)
+    } else {
+        format!("{}.{}{}{}", &digits[..1], &digits[1..], marker, exp)
+    }
+}
+
+// the digits of `n` in base `radix`, optionally without any trailing
+// zeros (but at least one digit).
+pub fn digit_string(n: &Int, radix: u32, upper: bool, trim: bool) -> String {
+    let mut s = n.to_str_radix(radix as u8, upper);
+    if trim {
+        let len = s.trim_right_matches('0').len();
+        s.truncate(cmp::max(len, 1));
+    }
+    s
+}
+
+// The shortest digits in base `radix` that round back to `x` (at its
+// precision, in the current exponent range, to nearest), as an
+// integer and the exponent of the leading digit, for a normal `x`.
+//
+// Having `n` digits works if either of the multiples of
+// `radix**(k - n + 1)` either side of `x` round back to it, and this
+// is monotonic in `n` (the multiples for `n` are also multiples for
+// `n + 1`), so the number of digits can be found by bisection.
+pub fn shortest_digits(x: &Float, radix: u32) -> (Int, i64) {
+    let (emin, emax) = Float::exp_range();
+    env::quietly(|| {
+        // values that aren't representable in the current range are
+        // treated as if there were no restriction. This probe is
+        // quiet, since formatting never raises exceptions.
+        let mut y = x.clone();
+        y.normalise_in(false, RoundingMode::NearestEven, emin, emax);
+        if y == *x {
+            Float::set_exp_range(emin, emax);
+        }
+        let mut lo = 0;
+        let mut hi = round_trip_digits(x.prec, radix);
+        let mut best = try_digits(x, radix, hi).expect("round trip digits don't round trip");
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            match try_digits(x, radix, mid) {
+                Some(found) => {
+                    hi = mid;
+                    best = found;
+                }
+                None => lo = mid,
+            }
+        }
+        best
+    })
+}
+
+// A value with `digits` digits in base `radix` that rounds back to
+// `x`, preferring the nearest (with ties away from zero, like the
+// shortest formatting of `f64`), if there is one.
+fn try_digits(x: &Float, radix: u32, digits: u32) -> Option<(Int, i64)> {
+    let (n, k) = to_digits(x, radix, digits, RoundingMode::NearestAway);
+    let lowest = Int::from(radix).pow(digits as usize - 1);
+
+    // `n` is the nearest, and the other side of `x` is either just
+    // above or just below it (in which case the step is smaller if
+    // `n` is a power of the radix).
+    let below = if n == lowest {
+        let mut m = &lowest * &Int::from(radix);
+        m -= 1;
+        (m, k - 1)
+    } else {
+        let mut m = n.clone();
+        m -= 1;
+        (m, k)
+    };
+    let mut above = n.clone();
+    above += 1;
+    let above = if above == &lowest * &Int::from(radix) {
+        (lowest, k + 1)
+    } else {
+        (above, k)
+    };
+    for (m, k) in vec![(n, k), below, above] {
+        let scale = k - (digits as i64 - 1);
+        if from_scaled(x.sign, m.clone(), radix, scale, x.prec, RoundingMode::NearestEven) == *x {
+            return Some((m, k))
+        }
+    }
+    None
+}
+
+// The number of digits after the point of an `ExactDecimal`
+// generated at a time, and of the first chunk of a very large integer
+// part.
+const EXACT_CHUNK: usize = 18;
+
+// Integer parts with more bits than this are generated a chunk at a
+// time from the top, rather than all converted at once.
+const EXACT_INT_BITS: i64 = 1 << 20;
+
+/// The exact decimal expansion of a `Float`, with every digit and no
+/// exponent, as created by `Float::exact_decimal`.
+///
+/// This is an iterator over the characters of the expansion, and
+/// also prints them all with `Display` (ignoring any width or
+/// precision). The digits after the point, and those of very large
+/// integer parts, are generated as they are needed, so the expansion
+/// of a value like `2**-1000000` (which has a million digits after
+/// the point) can be written out or examined a piece at a time, and
+/// the leading digits of one like `2**(2**62)` can be found at all.
+#[derive(Clone)]
+pub struct ExactDecimal {
+    // characters generated but not yet returned
+    buf: String,
+    pos: usize,
+    // the integer part, if it is too large to convert at once, which
+    // has `int_digits` digits, the leading `int_done` of which have
+    // been generated.
+    int: Float,
+    int_digits: u64,
+    int_done: u64,
+    // the remaining fractional part, `frac * 2**-frac_bits`
+    frac: Int,
+    frac_bits: usize,
+}
+
+impl ExactDecimal {
+    fn new(x: &Float) -> ExactDecimal {
+        let mut d = ExactDecimal {
+            buf: String::new(),
+            pos: 0,
+            int: Float::zero(1),
+            int_digits: 0,
+            int_done: 0,
+            frac: Int::zero(),
+            frac_bits: 0,
+        };
+        if let Style::NaN = x.style {
+            d.buf.push_str("NaN");
+            return d
+        }
+        if x.sign == Sign::Neg {
+            d.buf.push('-');
+        }
+        match x.style {
+            Style::NaN => unreachable!(),
+            Style::Infinity => d.buf.push_str("inf"),
+            Style::Zero => d.buf.push('0'),
+            Style::Normal => {
+                // |x| == signif * 2**shift
+                let shift = x.exp - (x.prec as i64 - 1);
+                if shift > EXACT_INT_BITS {
+                    // the leading digit has exponent `k`.
+                    d.int = x.clone().abs();
+                    let (_, k) = to_digits(&d.int, 10, 1, RoundingMode::TowardZero);
+                    d.int_digits = k as u64 + 1;
+                } else if shift >= 0 {
+                    let int = &x.signif << shift as usize;
+                    d.buf.push_str(&digit_string(&int, 10, false, false));
+                } else {
+                    let zeros = cmp::min(x.signif.trailing_zeros() as i64, -shift);
+                    let signif = &x.signif >> zeros as usize;
+                    let bits = (-shift - zeros) as usize;
+                    let int = &signif >> bits;
+                    d.frac = signif - &(&int << bits);
+                    d.frac_bits = bits;
+                    d.buf.push_str(&digit_string(&int, 10, false, false));
+                    if d.frac != 0 {
+                        d.buf.push('.');
+                    }
+                }
+            }
+        }
+        d
+    }
+
+    // Replace the buffer with the next digits of the integer or
+    // fractional part, returning false if there are none.
+    fn fill(&mut self) -> bool {
+        if self.int_done < self.int_digits {
+            // the leading `next` digits are `int * 10**-(int_digits -
+            // next)` truncated. Doubling how many there are each time
+            // keeps the total work to a constant multiple of that of
+            // the last conversion.
+            let next = cmp::min(self.int_done.saturating_mul(2) + EXACT_CHUNK as u64,
+                                self.int_digits);
+            let scale = (self.int_digits - next) as i64;
+            let n = env::quietly(|| round_scaled(&self.int, 10, scale, RoundingMode::TowardZero));
+            let digits = digit_string(&n, 10, false, false);
+            let len = (next - self.int_done) as usize;
+            self.buf = digits[digits.len() - len..].to_string();
+            self.int_done = next;
+            self.pos = 0;
+            return true
+        }
+        if self.frac == 0 {
+            return false
+        }
+        self.frac *= &Int::from(10u64.pow(EXACT_CHUNK as u32));
+        let int = &self.frac >> self.frac_bits;
+        self.frac -= &(&int << self.frac_bits);
+
+        let digits = digit_string(&int, 10, false, false);
+        self.buf = zeros(EXACT_CHUNK - digits.len()) + &digits;
+        if self.frac == 0 {
+            let len = self.buf.trim_right_matches('0').len();
+            self.buf.truncate(len);
+        }
+        self.pos = 0;
+        true
+    }
+}
+
+impl Iterator for ExactDecimal {
+    type Item = char;
+    fn next(&mut self) -> Option<char> {
+        while self.pos == self.buf.len() {
+            if !self.fill() {
+                return None
+            }
+        }
+        let c = self.buf[self.pos..].chars().next().unwrap();
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+}
+
+impl fmt::Display for ExactDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut d = self.clone();
+        loop {
+            try!(f.write_str(&d.buf[d.pos..]));
+            if !d.fill() {
+                return Ok(())
+            }
+        }
+    }
+}
+
+/// Prints the value in decimal, without an exponent.
+///
+/// With a precision (`{:.3}`), the value is correctly rounded (ties
+/// to even) to that many digits after the point. Otherwise, it is
+/// rounded to enough significant digits to uniquely identify it
+/// among values of its precision, with trailing zeros removed.
+///
+/// This writes out every digit, so values with very large or very
+/// small exponents should be printed with `{:e}` instead.
+impl fmt::Display for Float {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.debug_assert_valid();
+        let s = match self.style {
+            Style::NaN => return f.pad("NaN"),
+            Style::Infinity => "inf".to_string(),
+            Style::Zero => fixed_point("0".to_string(), f.precision().unwrap_or(0)),
+            Style::Normal => {
+                match f.precision() {
+                    Some(frac) => {
+                        let n = to_fixed(self, 10, frac as u32, RoundingMode::NearestEven);
+                        fixed_point(digit_string(&n, 10, false, false), frac)
+                    }
+                    None => {
+                        let digits = round_trip_digits(self.prec, 10);
+                        let (n, exp) = to_digits(self, 10, digits, RoundingMode::NearestEven);
+                        positional(&digit_string(&n, 10, false, true), exp)
+                    }
+                }
+            }
+        };
+        f.pad_integral(self.sign == Sign::Pos, "", &s)
+    }
+}
+
+impl Float {
+    /// The shortest decimal representation of `self`, in scientific
+    /// notation like `1.25e-3`, that parses back to exactly `self`
+    /// at its precision (rounding to nearest, in the current
+    /// exponent range).
+    ///
+    /// Of the strings with the fewest digits, this is the one
+    /// closest to `self`, with ties away from zero. Zero, infinities
+    /// and NaN are written as `0e0`, `inf` and `NaN`, with a `-` for
+    /// negative values.
+    pub fn to_shortest_decimal(&self) -> String {
+        self.debug_assert_valid();
+        let s = match self.style {
+            Style::NaN => return "NaN".to_string(),
+            Style::Infinity => "inf".to_string(),
+            Style::Zero => "0e0".to_string(),
+            Style::Normal => {
+                let (n, exp) = shortest_digits(self, 10);
+                scientific(&digit_string(&n, 10, false, true), exp, "e")
+            }
+        };
+        match self.sign {
+            Sign::Pos => s,
+            Sign::Neg => format!("-{}", s),
+        }
+    }
+
+    /// `self` in base `radix` with `digits` significant digits,
+    /// rounded according to `mode`, in scientific notation like
+    /// `1.2e-3` or `z.z@8`.
+    ///
+    /// The exponent is written in decimal and is a power of `radix`,
+    /// after an `e` in bases up to 10, and an `@` (as `e` would be a
+    /// digit) above that. Digits above 9 are lowercase letters. If
+    /// `digits` is zero, this uses enough digits to distinguish
+    /// `self` from any other value of its precision, with trailing
+    /// zeros removed. Infinity and NaN are written as `inf` and
+    /// `NaN` in bases up to 16, and `@inf@` and `@NaN@` above that.
+    /// This can be parsed by `from_str_radix`.
+    ///
+    /// This panics if `radix` is not between 2 and 36 (inclusive).
+    pub fn to_string_radix(&self, radix: u32, digits: u32, mode: RoundingMode) -> String {
+        assert!(2 <= radix && radix <= 36, "invalid radix {}", radix);
+        self.debug_assert_valid();
+        let marker = if radix <= 10 { "e" } else { "@" };
+        let s = match self.style {
+            Style::NaN => return if radix <= 16 { "NaN" } else { "@NaN@" }.to_string(),
+            Style::Infinity => if radix <= 16 { "inf" } else { "@inf@" }.to_string(),
+            Style::Zero => scientific(&zeros(cmp::max(digits, 1) as usize), 0, marker),
+            Style::Normal => {
+                let (digits, trim) = if digits == 0 {
+                    (round_trip_digits(self.prec, radix), true)
+                } else {
+                    (digits, false)
+                };
+                let (n, exp) = to_digits(self, radix, digits, mode);
+                scientific(&digit_string(&n, radix, false, trim), exp, marker)
+            }
+        };
+        match self.sign {
+            Sign::Pos => s,
+            Sign::Neg => format!("-{}", s),
+        }
+    }
+
+    /// `self` in base `radix` in the format written by MPFR's
+    /// `mpfr_out_str`, with `digits` significant digits rounded
+    /// according to `mode`, like `1.1010e4` or `-z.z@8`.
+    ///
+    /// This is the format of `to_string_radix`, except that if
+    /// `digits` is zero, this uses as many digits as MPFR does
+    /// (which are exact in bases that are powers of two), without
+    /// removing trailing zeros, and zero, infinity and NaN are `0`,
+    /// `@Inf@` and `@NaN@` (with a `-` for negative values). This can
+    /// be parsed by `from_mpfr_str`.
+    ///
+    /// This panics if `radix` is not between 2 and 36 (inclusive).
+    pub fn to_mpfr_string(&self, radix: u32, digits: u32, mode: RoundingMode) -> String {
+        assert!(2 <= radix && radix <= 36, "invalid radix {}", radix);
+        self.debug_assert_valid();
+        let s = match self.style {
+            Style::NaN => return "@NaN@".to_string(),
+            Style::Infinity => "@Inf@".to_string(),
+            Style::Zero => "0".to_string(),
+            Style::Normal => {
+                let digits = if digits == 0 { mpfr_digits(self.prec, radix) } else { digits };
+                let (n, exp) = to_digits(self, radix, digits, mode);
+                let marker = if radix <= 10 { "e" } else { "@" };
+                scientific(&digit_string(&n, radix, false, false), exp, marker)
+            }
+        };
+        match self.sign {
+            Sign::Pos => s,
+            Sign::Neg => format!("-{}", s),
+        }
+    }
+
+    /// `self` in base `radix` in the format written by GMP's
+    /// `mpf_out_str`, with `digits` significant digits rounded
+    /// according to `mode`, like `0.1101e5` or `-0.zz@9`.
+    ///
+    /// The digits all follow a `0.`, without trailing zeros, and the
+    /// exponent is written in decimal after an `e` in bases up to 10
+    /// and an `@` above that, so the value is `0.ddd * radix**exp`.
+    /// If `digits` is zero, this uses enough digits to distinguish
+    /// `self` from any other value of its precision. Zero is `0.e0`
+    /// (or `0.@0`), and as GMP has no infinities or NaN, they are
+    /// written like MPFR's, as `@Inf@` and `@NaN@`. This can be parsed
+    /// by `from_mpf_str` with a radix of `-radix`.
+    ///
+    /// This panics if `radix` is not between 2 and 36 (inclusive).
+    pub fn to_mpf_string(&self, radix: u32, digits: u32, mode: RoundingMode) -> String {
+        assert!(2 <= radix && radix <= 36, "invalid radix {}", radix);
+        self.debug_assert_valid();
+        let marker = if radix <= 10 { "e" } else { "@" };
+        let s = match self.style {
+            Style::NaN => return "@NaN@".to_string(),
+            Style::Infinity => "@Inf@".to_string(),
+            Style::Zero => format!("0.{}0", marker),
+            Style::Normal => {
+                let digits = if digits == 0 { round_trip_digits(self.prec, radix) } else { digits };
+                let (n, exp) = to_digits(self, radix, digits, mode);
+                format!("0.{}{}{}", digit_string(&n, radix, false, true), marker, exp + 1)
+            }
+        };
+        match self.sign {
+            Sign::Pos => s,
+            Sign::Neg => format!("-{}", s),
+        }
+    }
+
+    /// The exact value of `self` in decimal, like
+    /// `0.1000000000000000055511151231257827021181583404541015625`
+    /// for `Float::from(0.1)`, with no exponent and no trailing
+    /// zeros after the point.
+    ///
+    /// Every finite `Float` has a finite decimal expansion, although
+    /// it may be very long: a value less than one with lowest bit
+    /// `2**-n` has exactly `n` digits after the point.
+    pub fn exact_decimal(&self) -> ExactDecimal {
+        self.debug_assert_valid();
+        ExactDecimal::new(self)
+    }
+
+    fn fmt_exp(&self, f: &mut fmt::Formatter, marker: &str) -> fmt::Result {
+        self.debug_assert_valid();
+        let s = match self.style {
+            Style::NaN => return f.pad("NaN"),
+            Style::Infinity => "inf".to_string(),
+            Style::Zero => {
+                let digits = 1 + f.precision().unwrap_or(0);
+                scientific(&zeros(digits), 0, marker)
+            }
+            Style::Normal => {
+                let (digits, trim) = match f.precision() {
+                    Some(frac) => (frac as u32 + 1, false),
+                    None => (round_trip_digits(self.prec, 10), true),
+                };
+                let (n, exp) = to_digits(self, 10, digits, RoundingMode::NearestEven);
+                scientific(&digit_string(&n, 10, false, trim), exp, marker)
+            }
+        };
+        f.pad_integral(self.sign == Sign::Pos, "", &s)
+    }
+
+    fn fmt_hex(&self, f: &mut fmt::Formatter, upper: bool) -> fmt::Result {
+        self.debug_assert_valid();
+        let (prefix, marker) = if upper { ("0X", "P") } else { ("0x", "p") };
+        let s = match self.style {
+            Style::NaN => return f.pad("NaN"),
+            Style::Infinity => return f.pad_integral(self.sign == Sign::Pos, "", "inf"),
+            Style::Zero => {
+                let frac = f.precision().unwrap_or(0);
+                format!("{}{}+0", fixed_point(zeros(frac + 1), frac), marker)
+            }
+            Style::Normal => {
+                let (x, trim) = match f.precision() {
+                    Some(frac) => {
+                        let prec = 1 + 4 * frac as u32;
+                        let x = env::quietly(|| {
+                            self.clone().with_precision_round(prec, RoundingMode::NearestEven)
+                        });
+                        (x, false)
+                    }
+                    None => (self.clone(), true),
+                };
+                // the bits after the leading one, padded out to a
+                // whole number of hex digits.
+                let bits = x.prec as usize - 1;
+                let frac = (bits + 3) / 4;
+                let mut signif = x.signif.clone();
+                signif -= &(Int::from(1) << bits);
+                signif <<= 4 * frac - bits;
+
+                let mut digits = String::new();
+                if frac > 0 {
+                    let s = digit_string(&signif, 16, upper, false);
+                    digits = zeros(frac - s.len()) + &s;
+                }
+                if trim {
+                    let len = digits.trim_right_matches('0').len();
+                    digits.truncate(len);
+                }
+                let point = if digits.is_empty() { "" } else { "." };
+                format!("1{}{}{}{:+}", point, digits, marker, x.exp)
+            }
+        };
+
+        // zero padding goes between the prefix and the digits.
+        let sign = match self.sign {
+            Sign::Neg => "-",
+            Sign::Pos => if f.sign_plus() { "+" } else { "" },
+        };
+        let len = sign.len() + prefix.len() + s.len();
+        match f.width() {
+            Some(width) if f.sign_aware_zero_pad() && width > len => {
+                write!(f, "{}{}{}{}", sign, prefix, zeros(width - len), s)
+            }
+            _ => f.pad_integral(self.sign == Sign::Pos, "", &format!("{}{}", prefix, s)),
+        }
+    }
+}
+
+/// Prints the value in decimal scientific notation, like `1.25e-3`.
+///
+/// With a precision (`{:.3e}`), the value is correctly rounded (ties
+/// to even) to that many digits after the point. Otherwise, it has
+/// enough significant digits to uniquely identify it among values of
+/// its precision, with trailing zeros removed.
+impl fmt::LowerExp for Float {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_exp(f, "e")
+    }
+}
+
+/// Prints the value in decimal scientific notation, like `1.25E-3`,
+/// in the same way as `LowerExp`.
+impl fmt::UpperExp for Float {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_exp(f, "E")
+    }
+}
+
+/// Prints the value in hexadecimal scientific notation, like C's
+/// `%a`: `0x1.921fb54442d18p+1` is `0x1.921fb54442d18 * 2**1`.
+///
+/// The leading digit is always `1` (or `0` for zero). With a
+/// precision (`{:.3x}`), the value is correctly rounded (ties to
+/// even) to that many hex digits after the point. Otherwise, it is
+/// printed exactly, with trailing zeros removed. This can be parsed
+/// by `from_str_with_precision`.
+impl fmt::LowerHex for Float {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_hex(f, false)
+    }
+}
+
+/// Prints the value in hexadecimal scientific notation, like
+/// `0X1.921FB54442D18P+1`, in the same way as `LowerHex`.
+impl fmt::UpperHex for Float {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.fmt_hex(f, true)
+    }
+}
@@ -5,23 +5,44 @@
 extern crate ramp;
 extern crate ieee754;
 extern crate rand;
+#[cfg(feature = "serde")]
+extern crate serde;
 
 use ramp::Int;
-use std::{fmt, i64};
+use std::{cmp, fmt, i64};
+use std::cmp::Ordering;
 
 mod ops;
 
 mod sign;
 pub use sign::Sign;
 
+mod rounding;
+pub use rounding::RoundingMode;
+
+mod env;
+pub use env::{Exception, Flags};
+
+mod format;
+pub use format::ExactDecimal;
+
+mod parse;
+pub use parse::ParseFloatError;
+
+mod bytes;
+#[cfg(feature = "serde")]
+mod serde_impls;
+
 #[derive(Copy, Clone, Debug)]
 enum Style {
     NaN,
     Infinity,
     Zero,
+    // subnormals (only possible with a restricted exponent range, see
+    // `Float::set_exp_range`) are also `Normal`: the exponent is
+    // below the minimum, and the significand has low bits that are
+    // always zero.
     Normal,
-    // no subnormals... awkward to handle, and, the exponent goes down
-    // to -2**63 + 1, which is somewhat small.
 }
 
 pub struct Float {
@@ -48,13 +69,27 @@ impl Clone for Float {
     }
 }
 
+/// Prints the exact internal representation of the value, which can
+/// be parsed back with `Float::from_debug_str`.
+///
+/// A normal value with sign `+`, precision `p`, exponent `e` and
+/// significand `b` (written in binary, with exactly `p` digits, the
+/// first of which is `1`) is written `+b * 2^(e - p)`, e.g. `-1.5`
+/// with precision 4 is `-1100 * 2^(0 - 4)`. This represents
+/// `b * 2**(e - (p - 1))`. Zeros, infinities and NaN are written as
+/// `+0.0`, `-inf` and `NaN` followed by the precision, like
+/// `-0.0 (prec 4)`. A NaN with its sign bit set (e.g. by `negate`)
+/// is written `-NaN`.
 impl fmt::Debug for Float {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.debug_assert_valid();
         match self.style {
-            Style::NaN => write!(f, "NaN"),
-            Style::Zero => write!(f, "{}0.0", self.sign),
-            Style::Infinity => write!(f, "{}inf", self.sign),
+            Style::NaN => {
+                let sign = if self.sign == Sign::Neg { "-" } else { "" };
+                write!(f, "{}NaN (prec {})", sign, self.prec)
+            }
+            Style::Zero => write!(f, "{}0.0 (prec {})", self.sign, self.prec),
+            Style::Infinity => write!(f, "{}inf (prec {})", self.sign, self.prec),
             Style::Normal => write!(f, "{}{:b} * 2^({} - {})",
                                     self.sign, self.signif, self.exp, self.prec),
         }
@@ -70,11 +105,12 @@ impl Float {
         Float::zero_(p, Sign::Neg)
     }
 
+    /// The largest finite value, given the current exponent range.
     pub fn max(p: u32) -> Float {
         Float {
             prec: p,
             sign: Sign::Pos,
-            exp: i64::MAX - 1,
+            exp: Float::exp_range().1,
             signif: (Int::from(1) << p as usize) - 1,
             style: Style::Normal
         }
@@ -82,11 +118,13 @@ impl Float {
     pub fn min(p: u32) -> Float {
         -Float::max(p)
     }
+    /// The smallest positive normal value, given the current exponent
+    /// range.
     pub fn min_positive(p: u32) -> Float {
         Float {
             prec: p,
             sign: Sign::Pos,
-            exp: i64::MIN + 1,
+            exp: Float::exp_range().0,
             signif: (Int::from(1) << (p as usize - 1)),
             style: Style::Normal,
         }
@@ -127,7 +165,6 @@ impl Float {
         self
     }
     pub fn next_toward(self, target: &Float) -> Float {
-        use std::cmp::Ordering;
         match self.partial_cmp(target) {
             None | Some(Ordering::Equal) => self,
             Some(Ordering::Less) => self.next_above(),
@@ -183,33 +220,26 @@ impl Float {
         self.debug_assert_valid();
         self.prec
     }
-    pub fn with_precision(mut self, prec: u32) -> Float {
+    pub fn with_precision(self, prec: u32) -> Float {
+        self.with_precision_round(prec, RoundingMode::NearestEven)
+    }
+    /// Convert `self` to have precision `prec`, rounding according
+    /// to `mode` if this loses information.
+    pub fn with_precision_round(self, prec: u32, mode: RoundingMode) -> Float {
+        self.with_precision_ternary(prec, mode).0
+    }
+    /// Convert `self` to have precision `prec`, rounding according
+    /// to `mode`, and returning how the result compares to `self`.
+    pub fn with_precision_ternary(mut self, prec: u32, mode: RoundingMode) -> (Float, Ordering) {
         self.debug_assert_valid();
         assert!(prec > 0);
-        let old_prec = self.prec;
         self.prec = prec;
-        match self.style {
-            Style::NaN | Style::Infinity | Style::Zero => {}
-            Style::Normal => {
-                if prec < old_prec {
-                    // less precision:
-                    let trailing_zeros = self.signif.trailing_zeros();
-                    let has_trailing_one = trailing_zeros < old_prec - prec - 1;
-                    let half_ulp_bit = self.signif.bit(old_prec - prec - 1);
-                    let ulp_bit = self.signif.bit(old_prec - prec);
-                    let round = half_ulp_bit & (ulp_bit | has_trailing_one);
-
-                    self.signif >>= (old_prec - prec) as usize;
-                    if round {
-                        self.add_ulp();
-                    }
-                } else if prec > old_prec {
-                    self.signif <<= (prec - old_prec) as usize;
-                }
-            }
-        }
+        let ord = match self.style {
+            Style::NaN | Style::Infinity | Style::Zero => Ordering::Equal,
+            Style::Normal => self.normalise(false, mode),
+        };
         self.debug_assert_valid();
-        self
+        (self, ord)
     }
 
     // self * 2**exp
@@ -217,14 +247,30 @@ impl Float {
         self.debug_assert_valid();
         match self.style {
             Style::Normal => {
-                self.exp = self.exp.saturating_add(exp);
-                self.normalise(false);
+                let exp = exp_sum(self.exp, exp, 0);
+                self.normalise_at(exp, false, RoundingMode::NearestEven);
             }
             Style::NaN | Style::Infinity | Style::Zero => {}
         }
         self
     }
 
+    // NaN, as the result of an invalid operation.
+    fn invalid(p: u32) -> Float {
+        env::raise(Exception::Invalid);
+        Float::nan(p)
+    }
+    // the smallest non-zero (possibly subnormal) value with sign
+    // `sign`, when normal values have exponents at least `emin`.
+    fn tiny(p: u32, sign: Sign, emin: i64) -> Float {
+        Float {
+            prec: p,
+            sign: sign,
+            exp: tiny_exp(p, emin),
+            signif: (Int::from(1) << (p as usize - 1)),
+            style: Style::Normal,
+        }
+    }
     fn zero_(p: u32, sign: Sign) -> Float {
         Float {
             prec: p,
@@ -243,30 +289,153 @@ impl Float {
             style: Style::Infinity
         }
     }
-    fn normalise(&mut self, check_bits: bool) {
-        let diff = if check_bits {
-            let bits = self.signif.bit_length() as i64;
-            if self.signif == 0 {
-                *self = Float::zero_(self.prec, self.sign);
-                return
-            }
-            bits - self.prec as i64
+    // Round `self.signif`, which may have any non-zero number of
+    // bits, to `self.prec` bits (or fewer, if the result is
+    // subnormal) according to `mode`, returning how the result
+    // compares to the exact value. `self.exp` is the exponent of the
+    // leading bit of `self.signif`, and `sticky` is whether the exact
+    // value has any non-zero bits below those of `self.signif`.
+    fn normalise(&mut self, sticky: bool, mode: RoundingMode) -> Ordering {
+        let (emin, emax) = Float::exp_range();
+        self.normalise_in(sticky, mode, emin, emax)
+    }
+    // `normalise`, with the exponent range `emin..emax` rather than
+    // the current one.
+    fn normalise_in(&mut self, mut sticky: bool, mode: RoundingMode,
+                    emin: i64, emax: i64) -> Ordering {
+        if self.signif == 0 {
+            debug_assert!(!sticky);
+            *self = Float::zero_(self.prec, self.sign);
+            return Ordering::Equal
+        }
+        self.style = Style::Normal;
+        if self.exp > emax {
+            return self.overflow(mode, emax)
+        }
+
+        let prec = self.prec;
+        let is_tiny = self.exp < emin;
+        // how many bits the result can have: all of them for normal
+        // numbers, and fewer for subnormals. An exponent of
+        // `i64::MIN` is below any subnormal, so it is rounded as if
+        // normal, and then becomes zero below if it stays there.
+        let avail = if !is_tiny || self.exp == i64::MIN {
+            prec as i64
         } else {
-            0
+            self.exp.saturating_sub(tiny_exp(prec, emin)) + 1
         };
-        self.exp = self.exp.saturating_add(diff);
-        match self.exp {
-            // FIXME (#13)
-            i64::MAX => *self = Float::inf(self.prec, self.sign),
-            i64::MIN => *self = Float::zero_(self.prec, self.sign),
-            _ => {
-                use std::cmp::Ordering;
-                match diff.cmp(&0) {
-                    Ordering::Greater => self.signif >>= diff as usize,
-                    Ordering::Equal => {},
-                    Ordering::Less => self.signif <<= (-diff) as usize,
+
+        let bits = self.signif.bit_length();
+        if avail <= 0 {
+            // below half of the smallest subnormal (or exactly at it,
+            // if avail == 0), so the result is that or zero.
+            let half_ulp_bit = avail == 0;
+            sticky |= !half_ulp_bit || self.signif.trailing_zeros() < bits - 1;
+            let away = rounding::round_away(mode, self.sign, false, half_ulp_bit, sticky);
+            *self = if away {
+                Float::tiny(prec, self.sign, emin)
+            } else {
+                Float::zero_(prec, self.sign)
+            };
+            env::raise(Exception::Underflow);
+            env::raise(Exception::Inexact);
+            return rounding::ternary(self.sign, away, true)
+        }
+
+        let avail = avail as u32;
+        let mut half_ulp_bit = false;
+        if bits > avail {
+            let shift = bits - avail;
+            half_ulp_bit = self.signif.bit(shift - 1);
+            sticky |= self.signif.trailing_zeros() < shift - 1;
+            self.signif >>= shift as usize;
+        } else if bits < avail {
+            self.signif <<= (avail - bits) as usize;
+        }
+
+        let ulp_bit = self.signif.bit(0);
+        let away = rounding::round_away(mode, self.sign, ulp_bit, half_ulp_bit, sticky);
+        if away {
+            self.signif += 1;
+            if self.signif.bit(avail) {
+                self.signif >>= 1;
+                self.exp += 1;
+            }
+        }
+        self.signif <<= (prec - avail) as usize;
+
+        if self.exp > emax {
+            self.overflow(mode, emax)
+        } else if self.exp == i64::MIN {
+            self.underflow(mode, emin)
+        } else {
+            let inexact = half_ulp_bit || sticky;
+            if inexact {
+                if is_tiny {
+                    env::raise(Exception::Underflow);
                 }
+                env::raise(Exception::Inexact);
+            }
+            rounding::ternary(self.sign, away, inexact)
+        }
+    }
+    // `normalise`, with the exponent of the leading bit of
+    // `self.signif` given as computed by `exp_sum`.
+    fn normalise_at(&mut self, exp: (i64, bool), sticky: bool, mode: RoundingMode) -> Ordering {
+        let (emin, emax) = Float::exp_range();
+        match exp {
+            (i64::MAX, true) => self.overflow(mode, emax),
+            (_, true) => self.underflow(mode, emin),
+            (exp, false) => {
+                self.exp = exp;
+                self.normalise(sticky, mode)
+            }
+        }
+    }
+    // Replace `self` with the result of rounding a value too large
+    // to represent with exponent at most `emax`.
+    fn overflow(&mut self, mode: RoundingMode, emax: i64) -> Ordering {
+        let (prec, sign) = (self.prec, self.sign);
+        let away = rounding::round_away(mode, sign, true, true, true);
+        *self = if away {
+            Float::inf(prec, sign)
+        } else {
+            Float {
+                prec: prec,
+                sign: sign,
+                exp: emax,
+                signif: (Int::from(1) << prec as usize) - 1,
+                style: Style::Normal
             }
+        };
+        env::raise(Exception::Overflow);
+        env::raise(Exception::Inexact);
+        rounding::ternary(sign, away, true)
+    }
+    // Replace `self` with the result of rounding a non-zero value too
+    // small to represent, given normal exponents are at least `emin`.
+    fn underflow(&mut self, mode: RoundingMode, emin: i64) -> Ordering {
+        let (prec, sign) = (self.prec, self.sign);
+        let away = rounding::round_away(mode, sign, false, false, true);
+        *self = if away {
+            Float::tiny(prec, sign, emin)
+        } else {
+            Float::zero_(prec, sign)
+        };
+        env::raise(Exception::Underflow);
+        env::raise(Exception::Inexact);
+        rounding::ternary(sign, away, true)
+    }
+
+    // the number of low bits of the significand that must be zero,
+    // because `self` is subnormal.
+    fn subnormal_zeros(&self) -> u32 {
+        let emin = Float::exp_range().0;
+        if self.exp >= emin {
+            0
+        } else {
+            let avail = self.exp.saturating_sub(tiny_exp(self.prec, emin)) + 1;
+            self.prec - cmp::max(avail, 1) as u32
         }
     }
 
@@ -276,17 +445,14 @@ impl Float {
             Style::NaN => {},
             Style::Infinity => {}
             Style::Zero => {
-                let s = self.sign;
-                // FIXME (#13)
-                *self = Float::min_positive(self.prec);
-                self.sign = s;
+                *self = Float::tiny(self.prec, self.sign, Float::exp_range().0)
             }
             Style::Normal => {
-                self.signif += 1;
+                let ulp = Int::from(1) << self.subnormal_zeros() as usize;
+                self.signif += &ulp;
                 if self.signif.bit(self.prec) {
                     self.exp += 1;
-                    if self.exp == i64::MAX {
-                        // FIXME (#13)
+                    if self.exp > Float::exp_range().1 {
                         *self = Float::inf(self.prec, self.sign)
                     } else {
                         self.signif >>= 1;
@@ -300,24 +466,28 @@ impl Float {
         match self.style {
             Style::NaN => {},
             Style::Infinity => {
-                // FIXME (#13)
-                *self = Float::max(self.prec)
+                let s = self.sign;
+                *self = Float::max(self.prec);
+                self.sign = s;
             }
             Style::Zero => {
-                // FIXME (#13)
-                *self = Float::min_positive(self.prec);
-                self.sign = -self.sign;
+                *self = Float::tiny(self.prec, -self.sign, Float::exp_range().0)
             }
             Style::Normal => {
-                self.signif -= 1;
+                let emin = Float::exp_range().0;
+                let ulp = Int::from(1) << self.subnormal_zeros() as usize;
+                self.signif -= &ulp;
                 if !self.signif.bit(self.prec - 1) {
                     self.exp -= 1;
-                    if self.exp == i64::MIN {
-                        // FIXME (#13)
+                    if self.signif == 0 || self.exp < tiny_exp(self.prec, emin) {
                         *self = Float::zero_(self.prec, self.sign);
                     } else {
                         self.signif <<= 1;
-                        self.signif |= 1;
+                        // subnormals are evenly spaced, but normal
+                        // numbers get an extra bit of precision.
+                        if self.exp >= emin {
+                            self.signif |= 1;
+                        }
                     }
                 }
             }
@@ -349,13 +519,31 @@ impl Float {
     }
 }
 
+// the exponent of the smallest subnormal value with precision `prec`,
+// when normal values have exponents at least `emin`.
+fn tiny_exp(prec: u32, emin: i64) -> i64 {
+    cmp::max(emin.saturating_sub(prec as i64 - 1), i64::MIN + 1)
+}
+
 fn add_overflow(x: i64, y: i64) -> (i64, bool) {
     unsafe {
         std::intrinsics::add_with_overflow(x, y)
     }
 }
-fn sub_overflow(x: i64, y: i64) -> (i64, bool) {
-    unsafe {
-        std::intrinsics::sub_with_overflow(x, y)
+
+// x + y + z (assuming z is small), along with whether the true
+// result was out of range, in which case it is saturated to
+// `i64::MAX` or `i64::MIN`.
+fn exp_sum(x: i64, y: i64, z: i64) -> (i64, bool) {
+    let (raw, o1) = add_overflow(x, y);
+    let (adjusted, o2) = add_overflow(raw, z);
+    if o1 ^ o2 {
+        // if we only overflowed once, then there's a problem. A
+        // double overflow means we went over the limit and then
+        // back, but a single means we never returned.
+        let overflowed = if o1 { raw } else { adjusted };
+        (if overflowed < 0 { i64::MAX } else { i64::MIN }, true)
+    } else {
+        (adjusted, false)
     }
 }
@@ -1,6 +1,8 @@
-use {Style, Float};
+use {Style, Sign, Float, RoundingMode};
+use ramp::Int;
 
-use std::{cmp, u32};
+use std::{cmp, i64};
+use std::cmp::Ordering;
 use std::ops::{Add, AddAssign, Sub, SubAssign,
                Neg};
 
@@ -21,156 +23,204 @@ impl<'a> Neg for &'a Float {
 }
 
 #[derive(Copy, Clone)]
-enum Operation { Add, Sub }
-fn can_use_unsigned_add(x: &Float, y: &Float, op: Operation) -> bool {
-    x.exp <= y.exp && match op {
-        Operation::Add => x.sign == y.sign,
-        Operation::Sub => x.sign != y.sign
+pub enum Operation { Add, Sub }
+
+// Binary operations on values of different precisions produce a
+// result with the larger of the two, rounded once from the exact
+// value.
+impl Float {
+    /// Compute `self + other`, rounding according to `mode`.
+    pub fn add_round(self, other: &Float, mode: RoundingMode) -> Float {
+        self.add_ternary(other, mode).0
     }
-}
-fn can_use_unsigned_sub(x: &Float, y: &Float, op: Operation) -> bool {
-    x.exp >= y.exp && match op {
-        Operation::Add => x.sign != y.sign,
-        Operation::Sub => x.sign == y.sign
+    /// Compute `self - other`, rounding according to `mode`.
+    pub fn sub_round(self, other: &Float, mode: RoundingMode) -> Float {
+        self.sub_ternary(other, mode).0
+    }
+    /// Compute `self + other`, rounding according to `mode`, and
+    /// returning how the result compares to the exact sum.
+    pub fn add_ternary(mut self, other: &Float, mode: RoundingMode) -> (Float, Ordering) {
+        let prec = cmp::max(self.prec, other.prec);
+        let ord = add_in_place(&mut self, other, Operation::Add, prec, mode);
+        (self, ord)
+    }
+    /// Compute `self - other`, rounding according to `mode`, and
+    /// returning how the result compares to the exact difference.
+    pub fn sub_ternary(mut self, other: &Float, mode: RoundingMode) -> (Float, Ordering) {
+        let prec = cmp::max(self.prec, other.prec);
+        let ord = add_in_place(&mut self, other, Operation::Sub, prec, mode);
+        (self, ord)
+    }
+    /// Compute `x + y` rounded directly to `prec` bits according to
+    /// `mode`, independent of the precisions of `x` and `y`.
+    pub fn add_to(x: &Float, y: &Float, prec: u32, mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut x = x.clone();
+        add_in_place(&mut x, y, Operation::Add, prec, mode);
+        x
+    }
+    /// Compute `x - y` rounded directly to `prec` bits according to
+    /// `mode`, independent of the precisions of `x` and `y`.
+    pub fn sub_to(x: &Float, y: &Float, prec: u32, mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut x = x.clone();
+        add_in_place(&mut x, y, Operation::Sub, prec, mode);
+        x
     }
 }
-fn can_use_unsigned_op(x: &Float, y: &Float, op: Operation) -> bool {
-    can_use_unsigned_add(x, y, op) || can_use_unsigned_sub(x, y, op)
-}
 
-// this does an "unsigned" addition, which means it is just adding the
-// two significands directly, even if the signs of the two floats are
-// opposite (i.e. so a conventional addition would be a subtraction of
-// their significands)
-fn unsigned_add(x: &mut Float, y: &Float, op: Operation) {
-    debug_assert!(can_use_unsigned_add(x, y, op));
+// compute `x + y` or `x - y` (depending on `op`), rounded to `prec`
+// bits, storing the result in `x` and returning how it compares to
+// the exact value.
+fn add_in_place(x: &mut Float, y: &Float, op: Operation, prec: u32,
+                mode: RoundingMode) -> Ordering {
+    x.debug_assert_valid();
+    y.debug_assert_valid();
+
+    let y_sign = match op {
+        Operation::Add => y.sign,
+        Operation::Sub => -y.sign,
+    };
 
-    let diff = y.exp.saturating_sub(x.exp) as u64;
-    let diff = cmp::min(diff, u32::MAX as u64) as usize;
-    let mut half_ulp_bit = false;
-    let mut has_trailing_one = false;
-    if diff > 0 {
-        let half_ulp = diff as u32 - 1;
-        half_ulp_bit = x.signif.bit(half_ulp);
-        has_trailing_one = x.signif.trailing_zeros() < half_ulp;
+    match (x.style, y.style) {
+        (Style::NaN, _) | (_, Style::NaN) => *x = Float::nan(prec),
+        (Style::Infinity, Style::Infinity) => {
+            *x = if x.sign == y_sign { Float::inf(prec, x.sign) } else { Float::invalid(prec) }
+        }
+        (Style::Infinity, _) => *x = Float::inf(prec, x.sign),
+        (_, Style::Infinity) => *x = Float::inf(prec, y_sign),
+        (Style::Zero, Style::Zero) => {
+            let sign = if x.sign == y_sign { x.sign } else { exact_zero_sign(mode) };
+            *x = Float::zero_(prec, sign)
+        }
+        (Style::Zero, _) => {
+            x.clone_from(y);
+            x.sign = y_sign;
+            x.prec = prec;
+            return x.normalise(false, mode)
+        }
+        (_, Style::Zero) => {
+            x.prec = prec;
+            return x.normalise(false, mode)
+        }
+        (Style::Normal, Style::Normal) => return add_normal(x, y, y_sign, prec, mode),
     }
+    Ordering::Equal
+}
 
-    x.signif >>= diff;
-    x.signif += &y.signif;
-    x.exp = y.exp;
+// the sign of an exact zero result of adding two non-zero values, as
+// specified by IEEE 754.
+pub fn exact_zero_sign(mode: RoundingMode) -> Sign {
+    if mode == RoundingMode::TowardNegative { Sign::Neg } else { Sign::Pos }
+}
 
-    let overflowed = x.signif.bit(x.prec);
+// add two normal floats, treating `y` as having sign `y_sign`.
+fn add_normal(x: &mut Float, y: &Float, y_sign: Sign, prec: u32,
+              mode: RoundingMode) -> Ordering {
+    let mut y = Exact::new(y);
+    y.sign = y_sign;
+    let (result, ord) = add_exact(Exact::new(x), y, prec, mode);
+    *x = result;
+    ord
+}
 
-    let ulp_bit;
-    if overflowed {
-        has_trailing_one |= half_ulp_bit;
-        half_ulp_bit = x.signif.bit(0);
-        ulp_bit = x.signif.bit(1);
-    } else {
-        ulp_bit = x.signif.bit(0);
-    }
+// An exact non-zero value `signif * 2**low`, with the exponent stored
+// as an `Int`, since the exponent of a product (as in `mul_add`) may
+// be outside the range of `i64`.
+pub struct Exact {
+    pub sign: Sign,
+    pub signif: Int,
+    pub low: Int,
+}
 
-    if half_ulp_bit && (ulp_bit || has_trailing_one) {
-        x.signif += 1 << overflowed as u8;
+impl Exact {
+    pub fn new(x: &Float) -> Exact {
+        Exact {
+            sign: x.sign,
+            signif: x.signif.clone(),
+            low: Int::from(x.exp) - Int::from(x.prec - 1),
+        }
+    }
+    // the exponent of the leading bit.
+    fn high(&self) -> Int {
+        &self.low + &Int::from(self.signif.bit_length() - 1)
+    }
+    // the value rounded to `prec` bits, along with how it compares to
+    // the exact value.
+    pub fn round(self, prec: u32, mode: RoundingMode) -> (Float, Ordering) {
+        let high = self.high();
+        let exp = if high > Int::from(i64::MAX) {
+            (i64::MAX, true)
+        } else if high < Int::from(i64::MIN) {
+            (i64::MIN, true)
+        } else {
+            (i64::from(&high), false)
+        };
+        let mut x = Float {
+            prec: prec,
+            sign: self.sign,
+            exp: 0,
+            signif: self.signif,
+            style: Style::Normal,
+        };
+        let ord = x.normalise_at(exp, false, mode);
+        (x, ord)
     }
-    x.normalise(true);
 }
 
-fn unsigned_sub(x: &mut Float, y: &Float, op: Operation) {
-    debug_assert!(can_use_unsigned_sub(x, y, op));
+// `x + y` rounded to `prec` bits, along with how it compares to the
+// exact value.
+pub fn add_exact(x: Exact, y: Exact, prec: u32, mode: RoundingMode) -> (Float, Ordering) {
+    let (x_high, y_high) = (x.high(), y.high());
+    let (big, mut small, gap) = if x_high >= y_high {
+        (x, y, x_high - &y_high)
+    } else {
+        (y, x, y_high - &x_high)
+    };
 
-    let diff = x.exp.saturating_sub(y.exp) as u64;
-    let diff = cmp::min(diff, u32::MAX as u64) as u32;
-    if diff <= x.prec + 2 {
-        x.exp = y.exp;
-        // FIXME(#14)
-        x.signif <<= diff as usize;
-        x.signif -= &y.signif;
-        if x.signif.sign() < 0 {
-            x.signif.negate();
-            x.sign = -x.sign;
-        }
-        let shift = x.signif.bit_length() as i64 - x.prec as i64;
-        if shift > 0 {
-            x.exp = x.exp.saturating_add(shift);
-            let shift = shift as u32;
-            let ulp_bit = x.signif.bit(shift);
-            let half_ulp_bit = x.signif.bit(shift - 1);
-            let has_trailing_ones = x.signif.trailing_zeros() < shift - 1;
+    // A value with its leading bit at least this far below the
+    // larger one is strictly smaller than both the lowest bit of the
+    // larger one and half an ulp of the result, so it can only
+    // influence the rounding through its sign: it can be replaced by
+    // a single bit at this position without changing the result.
+    let limit = cmp::max(big.signif.bit_length(), prec + 2);
+    if gap >= Int::from(limit) {
+        small.signif = Int::from(1);
+        small.low = big.high() - Int::from(limit);
+    }
 
-            x.signif >>= shift as usize;
+    let low = if big.low <= small.low { big.low.clone() } else { small.low.clone() };
+    let mut sum = big.signif << i64::from(&(big.low - &low)) as usize;
+    if big.sign == Sign::Neg {
+        sum.negate();
+    }
+    let mut signif = small.signif << i64::from(&(small.low - &low)) as usize;
+    if small.sign == Sign::Neg {
+        signif.negate();
+    }
+    sum += &signif;
 
-            let round = half_ulp_bit && (ulp_bit || has_trailing_ones);
-            if round {
-                x.signif += 1;
-            }
-        }
+    if sum == 0 {
+        return (Float::zero_(prec, exact_zero_sign(mode)), Ordering::Equal)
     }
-    x.normalise(true);
+    let sign = if sum.sign() < 0 {
+        sum.negate();
+        Sign::Neg
+    } else {
+        Sign::Pos
+    };
+    Exact { sign: sign, signif: sum, low: low }.round(prec, mode)
 }
 
 impl AddAssign<Float> for Float {
-    fn add_assign(&mut self, mut other: Float) {
-        self.debug_assert_valid();
-        other.debug_assert_valid();
-        assert_eq!(self.prec, other.prec);
-        let prec = self.prec;
-
-        match (self.style, other.style) {
-            (Style::NaN, _) | (_, Style::NaN) => *self = Float::nan(prec),
-            (Style::Infinity, Style::Infinity) => {
-                *self = if self.sign == other.sign { Float::inf(prec, self.sign) } else { Float::nan(prec) }
-            }
-            (Style::Infinity, _) => *self = Float::inf(prec, self.sign),
-            (_, Style::Infinity) => *self = Float::inf(prec, other.sign),
-            (Style::Zero, _) => *self = other,
-            (_, Style::Zero) => {}
-            (Style::Normal, Style::Normal) => {
-                if can_use_unsigned_add(self, &other, Operation::Add) {
-                    unsigned_add(self, &other, Operation::Add)
-                } else if can_use_unsigned_sub(self, &other, Operation::Add) {
-                    unsigned_sub(self, &other, Operation::Add)
-                } else {
-                    if can_use_unsigned_add(&other, self, Operation::Add) {
-                        unsigned_add(&mut other, self, Operation::Add)
-                    } else {
-                        unsigned_sub(&mut other, self, Operation::Add)
-                    }
-                    *self = other;
-                }
-            }
-        }
+    fn add_assign(&mut self, other: Float) {
+        *self += &other
     }
 }
 impl<'a> AddAssign<&'a Float> for Float {
     fn add_assign(&mut self, other: &'a Float) {
-        self.debug_assert_valid();
-        other.debug_assert_valid();
-        assert_eq!(self.prec, other.prec);
-        let prec = self.prec;
-
-        match (self.style, other.style) {
-            (Style::NaN, _) | (_, Style::NaN) => *self = Float::nan(prec),
-            (Style::Infinity, Style::Infinity) => {
-                *self = if self.sign == other.sign { Float::inf(prec, self.sign) } else { Float::nan(prec) }
-            }
-            (Style::Infinity, _) => *self = Float::inf(prec, self.sign),
-            (_, Style::Infinity) => *self = Float::inf(prec, other.sign),
-            (Style::Zero, _) => {
-                self.clone_from(other);
-            }
-            (_, Style::Zero) => {},
-            (Style::Normal, Style::Normal) => {
-                if can_use_unsigned_add(self, other, Operation::Add) {
-                    unsigned_add(self, other, Operation::Add);
-                } else if can_use_unsigned_sub(self, other, Operation::Add) {
-                    unsigned_sub(self, other, Operation::Add);
-                } else {
-                    // FIXME(#15)
-                    *self += other.clone()
-                }
-            }
-        }
+        let prec = cmp::max(self.prec, other.prec);
+        add_in_place(self, other, Operation::Add, prec, RoundingMode::NearestEven);
     }
 }
 
@@ -200,27 +250,19 @@ impl<'a> Add<Float> for &'a Float {
 impl<'a> Add<&'a Float> for &'a Float {
     type Output = Float;
     fn add(self, other: &'a Float) -> Float {
-        let clone_self = can_use_unsigned_op(self, other, Operation::Add);
-
-        if clone_self {
-            self.clone() + other
-        } else {
-            other.clone() + self
-        }
+        self.clone() + other
     }
 }
 
 impl SubAssign<Float> for Float {
     fn sub_assign(&mut self, other: Float) {
-        *self += -other
+        *self -= &other
     }
 }
 impl<'a> SubAssign<&'a Float> for Float {
     fn sub_assign(&mut self, other: &'a Float) {
-        // self - other == -(-self + other)
-        self.negate();
-        *self += other;
-        self.negate();
+        let prec = cmp::max(self.prec, other.prec);
+        add_in_place(self, other, Operation::Sub, prec, RoundingMode::NearestEven);
     }
 }
 
@@ -228,7 +270,7 @@ impl Sub<Float> for Float {
     type Output = Float;
 
     fn sub(mut self, other: Float) -> Float {
-        self += -other;
+        self -= &other;
         self
     }
 }
@@ -241,20 +283,13 @@ impl<'a> Sub<&'a Float> for Float {
 }
 impl<'a> Sub<Float> for &'a Float {
     type Output = Float;
-    fn sub(self, mut other: Float) -> Float {
-        other -= self;
-        other.negate();
-        other
+    fn sub(self, other: Float) -> Float {
+        self.clone() - &other
     }
 }
 impl<'a> Sub<&'a Float> for &'a Float {
     type Output = Float;
     fn sub(self, other: &'a Float) -> Float {
-        let clone_self = can_use_unsigned_op(self, other, Operation::Sub);
-        if clone_self {
-            self.clone() - other
-        } else {
-            -(other.clone() - self)
-        }
+        self.clone() - other
     }
 }
@@ -14,7 +14,6 @@ impl PartialOrd for Float {
     fn partial_cmp(&self, other: &Float) -> Option<Ordering> {
         self.debug_assert_valid();
         other.debug_assert_valid();
-        assert_eq!(self.prec, other.prec);
 
         Some(match (self.style, other.style) {
             (Style::NaN, _) | (_, Style::NaN) => return None,
@@ -34,26 +33,26 @@ impl PartialOrd for Float {
             }
             // |LHS| > |RHS| guaranteed
             (Style::Normal, Style::Zero) => {
-                match self.sign.cmp(&other.sign) {
-                    Ordering::Less => Ordering::Less,
-                    Ordering::Equal | Ordering::Greater => Ordering::Greater
+                match self.sign {
+                    Sign::Pos => Ordering::Greater,
+                    Sign::Neg => Ordering::Less
                 }
             }
 
             // |LHS| < |RHS| guaranteed
             (Style::Zero, Style::Normal) => {
-                match self.sign.cmp(&other.sign) {
-                    Ordering::Less | Ordering::Equal => Ordering::Less,
-                    Ordering::Greater => Ordering::Greater
+                match other.sign {
+                    Sign::Pos => Ordering::Less,
+                    Sign::Neg => Ordering::Greater
                 }
             }
 
             (Style::Zero, Style::Zero) => Ordering::Equal,
             (Style::Normal, Style::Normal) => {
                 match (self.sign, other.sign) {
-                    (Sign::Pos, Sign::Pos) => (self.exp, &self.signif).cmp(&(other.exp, &other.signif)),
+                    (Sign::Pos, Sign::Pos) => cmp_abs(self, other),
                     (Sign::Pos, Sign::Neg) => Ordering::Greater,
-                    (Sign::Neg, Sign::Neg) => (other.exp, &other.signif).cmp(&(self.exp, &self.signif)),
+                    (Sign::Neg, Sign::Neg) => cmp_abs(other, self),
                     (Sign::Neg, Sign::Pos) => Ordering::Less,
                 }
             }
@@ -61,41 +60,53 @@ impl PartialOrd for Float {
     }
 }
 
+// compare the magnitudes of two normal floats, which may have
+// different precisions.
+fn cmp_abs(x: &Float, y: &Float) -> Ordering {
+    match x.exp.cmp(&y.exp) {
+        Ordering::Equal => {}
+        ord => return ord
+    }
+    // line up the significands, so that their leading bits match.
+    match x.prec.cmp(&y.prec) {
+        Ordering::Equal => x.signif.cmp(&y.signif),
+        Ordering::Less => (&x.signif << (y.prec - x.prec) as usize).cmp(&y.signif),
+        Ordering::Greater => x.signif.cmp(&(&y.signif << (x.prec - y.prec) as usize)),
+    }
+}
+
 // FIXME (#4): these shouldn't need to allocate.
-// FIXME (#5): these probably shouldn't truncate the Float if its precision
-// is less than the input (e.g. 0b1111_i32 !=
-// Float::from(0b1111_i32).with_precision(3))
 macro_rules! prim_cmp {
     ($($t: ty),*) => {
         $(
         impl PartialEq<$t> for Float {
             fn eq(&self, other: &$t) -> bool {
-                *self == Float::from(*other).with_precision(self.precision())
+                *self == Float::from(*other)
             }
 
             fn ne(&self, other: &$t) -> bool {
-                *self != Float::from(*other).with_precision(self.precision())
+                *self != Float::from(*other)
             }
         }
 
         impl PartialEq<Float> for $t {
             fn eq(&self, other: &Float) -> bool {
-                Float::from(*self).with_precision(other.precision()) == *other
+                Float::from(*self) == *other
             }
 
             fn ne(&self, other: &Float) -> bool {
-                Float::from(*self).with_precision(other.precision()) != *other
+                Float::from(*self) != *other
             }
         }
 
         impl PartialOrd<$t> for Float {
             fn partial_cmp(&self, other: &$t) -> Option<Ordering> {
-                self.partial_cmp(&Float::from(*other).with_precision(self.precision()))
+                self.partial_cmp(&Float::from(*other))
             }
         }
         impl PartialOrd<Float> for $t {
             fn partial_cmp(&self, other: &Float) -> Option<Ordering> {
-                Float::from(*self).with_precision(other.precision()).partial_cmp(other)
+                Float::from(*self).partial_cmp(other)
             }
         }
             )*
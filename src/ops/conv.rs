@@ -1,4 +1,4 @@
-use {Style, Sign, Float};
+use {Style, Sign, Float, RoundingMode};
 use ramp::Int;
 
 use std::{f64, f32};
@@ -52,8 +52,17 @@ fn from_ieee754(prec: u32, exp_width: u32, is_negative: bool, exp: i64, signif:
         if signif == 0 {
             Float::zero_(prec, sign)
         } else {
-            // subnormal
-            unimplemented!()
+            // subnormal: the value is signif * 2**(min + 1 - (prec -
+            // 1)), which fits exactly in `prec` bits.
+            let bits = 64 - signif.leading_zeros();
+            let shift = prec - bits;
+            Float {
+                prec: prec,
+                sign: sign,
+                signif: Int::from(signif << shift),
+                exp: min + 1 - shift as i64,
+                style: Style::Normal
+            }
         }
     } else if exp == max {
         if signif == 0 {
@@ -87,47 +96,71 @@ impl From<f32> for Float {
     }
 }
 
-impl From<Float> for f64 {
-    fn from(f: Float) -> f64 {
-        f.debug_assert_valid();
-        let f = f.with_precision(53);
+// round `f` to the values representable in an IEEE 754 format with
+// `prec` significand bits (including the implicit one) and
+// `exp_width` exponent bits.
+fn round_ieee754(mut f: Float, prec: u32, exp_width: u32, mode: RoundingMode) -> Float {
+    f.debug_assert_valid();
+    let emax = (1 << (exp_width - 1)) - 1;
+    f.prec = prec;
+    if let Style::Normal = f.style {
+        f.normalise_in(false, mode, 1 - emax, emax);
+    }
+    f
+}
+
+// the raw (unbiased) exponent and the significand bits (excluding the
+// implicit one) of the finite non-zero `f`, as rounded by
+// `round_ieee754`.
+fn ieee754_parts(f: &Float, exp_width: u32) -> (i64, u64) {
+    let emin = 2 - (1 << (exp_width - 1));
+    let signif: u64 = (&f.signif).into();
+    if f.exp < emin {
+        // subnormal, so the bits shifted out are all zero
+        (emin - 1, signif >> (emin - f.exp) as u32)
+    } else {
+        (f.exp, signif & ((1 << (f.prec - 1)) - 1))
+    }
+}
+
+impl Float {
+    /// Convert `self` to the nearest `f64` in the direction given
+    /// by `mode`, including subnormal values.
+    pub fn to_f64_round(self, mode: RoundingMode) -> f64 {
+        let f = round_ieee754(self, 53, 11, mode);
         match f.style {
             Style::NaN => f64::NAN,
             Style::Infinity => f64::INFINITY * f.sign as i32 as f64,
             Style::Zero => 0.0 * f.sign as i32 as f64,
             Style::Normal => {
-                if f.exp > 1023 {
-                    f64::INFINITY * f.sign as i32 as f64
-                } else if f.exp < -1023 {
-                    0.0 * f.sign as i32 as f64
-                } else {
-                    let mut raw = (&f.signif).into();
-                    raw &= (1 << 52) - 1;
-                    f64::recompose(f.sign == Sign::Neg, f.exp as i16, raw)
-                }
+                let (exp, raw) = ieee754_parts(&f, 11);
+                f64::recompose(f.sign == Sign::Neg, exp as i16, raw)
             }
         }
     }
-}
-impl From<Float> for f32 {
-    fn from(f: Float) -> f32 {
-        f.debug_assert_valid();
-        let f = f.with_precision(24);
+    /// Convert `self` to the nearest `f32` in the direction given
+    /// by `mode`, including subnormal values.
+    pub fn to_f32_round(self, mode: RoundingMode) -> f32 {
+        let f = round_ieee754(self, 24, 8, mode);
         match f.style {
             Style::NaN => f32::NAN,
             Style::Infinity => f32::INFINITY * f.sign as i32 as f32,
             Style::Zero => 0.0 * f.sign as i32 as f32,
             Style::Normal => {
-                if f.exp > 127 {
-                    f32::INFINITY * f.sign as i32 as f32
-                } else if f.exp < -127 {
-                    0.0 * f.sign as i32 as f32
-                } else {
-                    let mut raw = (&f.signif).into();
-                    raw &= (1 << 23) - 1;
-                    f32::recompose(f.sign == Sign::Neg, f.exp as i16, raw)
-                }
+                let (exp, raw) = ieee754_parts(&f, 8);
+                f32::recompose(f.sign == Sign::Neg, exp as i16, raw as u32)
             }
         }
     }
 }
+
+impl From<Float> for f64 {
+    fn from(f: Float) -> f64 {
+        f.to_f64_round(RoundingMode::NearestEven)
+    }
+}
+impl From<Float> for f32 {
+    fn from(f: Float) -> f32 {
+        f.to_f32_round(RoundingMode::NearestEven)
+    }
+}
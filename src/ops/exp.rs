@@ -0,0 +1,653 @@
+use {Style, Sign, Float, RoundingMode, exp_sum};
+use env;
+use format::pow_bounds;
+use super::log::ln10_bounds;
+use ramp::Int;
+
+use std::{cmp, i64};
+use std::cmp::Ordering;
+
+impl Float {
+    /// Compute `e**self`, rounding to nearest.
+    pub fn exp(self) -> Float {
+        self.exp_round(RoundingMode::NearestEven)
+    }
+    /// Compute `e**self`, rounding according to `mode`.
+    pub fn exp_round(self, mode: RoundingMode) -> Float {
+        self.exp_ternary(mode).0
+    }
+    /// Compute `e**self`, rounding according to `mode`, and
+    /// returning how the result compares to the exact value.
+    pub fn exp_ternary(mut self, mode: RoundingMode) -> (Float, Ordering) {
+        let prec = self.prec;
+        let ord = exp_in_place(&mut self, prec, mode);
+        (self, ord)
+    }
+    /// Compute `e**x` rounded directly to `prec` bits according to
+    /// `mode`, independent of the precision of `x`.
+    pub fn exp_to(x: &Float, prec: u32, mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut x = x.clone();
+        exp_in_place(&mut x, prec, mode);
+        x
+    }
+
+    /// Compute `e**self - 1`, rounding to nearest. Unlike
+    /// `self.exp() - 1`, this is accurate when `self` is near zero.
+    pub fn expm1(self) -> Float {
+        self.expm1_round(RoundingMode::NearestEven)
+    }
+    /// Compute `e**self - 1`, rounding according to `mode`.
+    pub fn expm1_round(self, mode: RoundingMode) -> Float {
+        self.expm1_ternary(mode).0
+    }
+    /// Compute `e**self - 1`, rounding according to `mode`, and
+    /// returning how the result compares to the exact value.
+    pub fn expm1_ternary(mut self, mode: RoundingMode) -> (Float, Ordering) {
+        let prec = self.prec;
+        let ord = expm1_in_place(&mut self, prec, mode);
+        (self, ord)
+    }
+    /// Compute `e**x - 1` rounded directly to `prec` bits according
+    /// to `mode`, independent of the precision of `x`.
+    pub fn expm1_to(x: &Float, prec: u32, mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut x = x.clone();
+        expm1_in_place(&mut x, prec, mode);
+        x
+    }
+
+    /// Compute `2**self`, rounding to nearest. This is exact if
+    /// `self` is an integer (and the result is in range).
+    pub fn exp2(self) -> Float {
+        self.exp2_round(RoundingMode::NearestEven)
+    }
+    /// Compute `2**self`, rounding according to `mode`.
+    pub fn exp2_round(self, mode: RoundingMode) -> Float {
+        self.exp2_ternary(mode).0
+    }
+    /// Compute `2**self`, rounding according to `mode`, and
+    /// returning how the result compares to the exact value.
+    pub fn exp2_ternary(mut self, mode: RoundingMode) -> (Float, Ordering) {
+        let prec = self.prec;
+        let ord = exp2_in_place(&mut self, prec, mode);
+        (self, ord)
+    }
+    /// Compute `2**x` rounded directly to `prec` bits according to
+    /// `mode`, independent of the precision of `x`.
+    pub fn exp2_to(x: &Float, prec: u32, mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut x = x.clone();
+        exp2_in_place(&mut x, prec, mode);
+        x
+    }
+
+    /// Compute `10**self`, rounding to nearest. This is exact if
+    /// `self` is a non-negative integer and the result fits in the
+    /// precision.
+    pub fn exp10(self) -> Float {
+        self.exp10_round(RoundingMode::NearestEven)
+    }
+    /// Compute `10**self`, rounding according to `mode`.
+    pub fn exp10_round(self, mode: RoundingMode) -> Float {
+        self.exp10_ternary(mode).0
+    }
+    /// Compute `10**self`, rounding according to `mode`, and
+    /// returning how the result compares to the exact value.
+    pub fn exp10_ternary(mut self, mode: RoundingMode) -> (Float, Ordering) {
+        let prec = self.prec;
+        let ord = exp10_in_place(&mut self, prec, mode);
+        (self, ord)
+    }
+    /// Compute `10**x` rounded directly to `prec` bits according to
+    /// `mode`, independent of the precision of `x`.
+    pub fn exp10_to(x: &Float, prec: u32, mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut x = x.clone();
+        exp10_in_place(&mut x, prec, mode);
+        x
+    }
+}
+
+const DOWN: RoundingMode = RoundingMode::TowardNegative;
+const UP: RoundingMode = RoundingMode::TowardPositive;
+
+// Round `sign * y * 2**scale` to `prec` bits according to `mode`,
+// returning how the result compares to the exact value, where
+// `bounds(working)` computes `lo` and `hi` with about `working` bits
+// of precision such that `0 < lo < y < hi`. These are computed with
+// more and more precision until they round to the same value, so `y`
+// must not be exactly representable (i.e. it must be irrational, or
+// at least have a lot of bits), or this never finishes.
+//
+// `bounds` is run inside `env::quietly`, but the rounding is done in
+// the caller's exponent range, like any other operation.
+pub fn round_bounds<F>(sign: Sign, scale: i64, prec: u32, mode: RoundingMode,
+                       mut bounds: F) -> (Float, Ordering)
+    where F: FnMut(u32) -> (Float, Float)
+{
+    let (emin, emax) = Float::exp_range();
+    let (emin, emax) = (emin.saturating_sub(scale), emax.saturating_sub(scale));
+
+    let mut lo = env::quietly(|| {
+        let mut working = prec + 32;
+        loop {
+            let (mut lo, hi) = bounds(working);
+            lo.sign = sign;
+            let mut rounded_lo = lo.clone();
+            rounded_lo.prec = prec;
+            rounded_lo.normalise_in(true, mode, emin, emax);
+
+            // the exact value is just above `lo`, which is what the
+            // sticky bit represents, but it is below `hi`, so use a
+            // value between `hi` and the next value below it with a
+            // few more bits than `prec` instead, which rounds the
+            // same as anything just below `hi`.
+            let mut below = hi;
+            below.sign = sign;
+            let extra = cmp::max(prec as i64 + 2 - below.prec as i64, 1);
+            below.signif = (below.signif << extra as usize) - Int::from(1);
+            // the leading bit moves down if `hi` is a power of two
+            below.exp -= below.prec as i64 + extra - below.signif.bit_length() as i64;
+            below.prec = prec;
+            below.normalise_in(true, mode, emin, emax);
+
+            if rounded_lo == below {
+                return lo
+            }
+            working *= 2;
+        }
+    });
+
+    let exp = exp_sum(lo.exp, scale, 0);
+    lo.prec = prec;
+    let ord = lo.normalise_at(exp, true, mode);
+    (lo, ord)
+}
+
+// `ln(2) * 2**bits` rounded down and up, using
+//
+//     ln(2) == 2 * atanh(1/3) == 2 * sum(1 / ((2k + 1) * 3**(2k + 1)))
+fn ln2_fixed(bits: u32) -> (Int, Int) {
+    let nine = Int::from(9);
+    // this is exactly floor(2**bits / 3**(2k + 1)), since repeatedly
+    // flooring a quotient is the same as flooring it once.
+    let mut power = (Int::from(1) << bits as usize).divmod(&Int::from(3)).0;
+    let mut sum = Int::zero();
+    let mut terms = 0;
+    while power != 0 {
+        sum += &power.divmod(&Int::from(2 * terms + 1)).0;
+        power = power.divmod(&nine).0;
+        terms += 1;
+    }
+    // each term is below the true one by less than one, and the
+    // terms left out sum to less than 9/8.
+    let lo = sum << 1;
+    let hi = &lo + &Int::from(2 * terms + 4);
+    (lo, hi)
+}
+
+// `ln(2)` rounded down and up to `prec` bits.
+pub fn ln2_bounds(prec: u32) -> (Float, Float) {
+    // the error in `ln2_fixed` is much less than 2**32 units
+    let bits = prec + 32;
+    let (lo, hi) = ln2_fixed(bits);
+    (Float::from(lo).with_precision_round(prec, DOWN).mul_exp2(-(bits as i64)),
+     Float::from(hi).with_precision_round(prec, UP).mul_exp2(-(bits as i64)))
+}
+
+// an integer near `x / ln(2)` (within a few units), for a normal `x`
+// with `|x| < 2**63`, if it fits in an `i64`.
+pub fn div_ln2(x: &Float) -> Option<i64> {
+    if x.exp < -64 {
+        return Some(0)
+    }
+    // x * 2**64, truncated, divided by ln(2) * 2**128
+    let shift = x.exp - (x.prec as i64 - 1) + 64;
+    let mut fixed = if shift >= 0 {
+        &x.signif << shift as usize
+    } else {
+        &x.signif >> -shift as usize
+    };
+    if x.sign == Sign::Neg {
+        fixed.negate();
+    }
+    let k = (fixed << 64).divmod(&ln2_fixed(128).0).0;
+    if Int::from(i64::MIN) <= k && k <= Int::from(i64::MAX) {
+        Some(i64::from(&k))
+    } else {
+        None
+    }
+}
+
+// bounds on `x - k * ln(2)`, rounded down and up to `prec` bits.
+pub fn sub_ln2_bounds(x: &Float, k: i64, prec: u32) -> (Float, Float) {
+    if k == 0 {
+        return (x.clone(), x.clone())
+    }
+    // the products below are exact, so all of the error comes from
+    // `ln(2)`, which needs more bits the larger `k` is.
+    let k_bits = 64 - k.wrapping_abs().leading_zeros();
+    let (ln2_lo, ln2_hi) = ln2_bounds(prec + k_bits + 8);
+    let (big, small) = if k > 0 { (ln2_hi, ln2_lo) } else { (ln2_lo, ln2_hi) };
+    let k = Float::from(k);
+    let work = prec + 2 * k_bits + 8;
+    let nearest = RoundingMode::NearestEven;
+    (Float::sub_to(x, &Float::mul_to(&k, &big, work, nearest), prec, DOWN),
+     Float::sub_to(x, &Float::mul_to(&k, &small, work, nearest), prec, UP))
+}
+
+// `e**t - 1` rounded down and up to about `prec` bits, for `t > 0`.
+pub fn expm1_pos_bounds(t: &Float, prec: u32) -> (Float, Float) {
+    debug_assert!(t.sign == Sign::Pos);
+    // t == a * 2**s, where `a` is small enough for the series to
+    // converge quickly, and then e**t - 1 is computed from
+    // e**a - 1 with `s` steps of e**2b - 1 == (e**b - 1) (e**b + 1).
+    let m = cmp::max((prec as f64).sqrt() as i64, 2);
+    let s = cmp::max(t.exp.saturating_add(m), 0);
+    let a = t.clone().mul_exp2(-s);
+    // each step of the doubling can double the relative error.
+    let work = prec + s as u32 + 32;
+
+    let (mut lo, mut hi) = (a.clone(), a.clone());
+    let (mut lo_term, mut hi_term) = (a.clone(), a.clone());
+    let mut n = 1u32;
+    loop {
+        n += 1;
+        let divisor = Float::from(n);
+        lo_term = Float::div_to(&Float::mul_to(&lo_term, &a, work, DOWN), &divisor, work, DOWN);
+        hi_term = Float::div_to(&Float::mul_to(&hi_term, &a, work, UP), &divisor, work, UP);
+        lo = Float::add_to(&lo, &lo_term, work, DOWN);
+        hi = Float::add_to(&hi, &hi_term, work, UP);
+        if hi_term.exp < hi.exp.saturating_sub(work as i64) {
+            break
+        }
+    }
+    // `a < 1/2`, so the rest of the series sums to less than the last
+    // term.
+    hi = Float::add_to(&hi, &hi_term, work, UP);
+
+    let two = Float::from(2);
+    for _ in 0..s {
+        lo = Float::mul_to(&lo, &Float::add_to(&lo, &two, work, DOWN), work, DOWN);
+        hi = Float::mul_to(&hi, &Float::add_to(&hi, &two, work, UP), work, UP);
+    }
+    (lo, hi)
+}
+
+// `e**r` rounded down and up to about `prec` bits.
+pub fn exp_bounds(r: &Float, prec: u32) -> (Float, Float) {
+    let one = Float::from(1);
+    if let Style::Zero = r.style {
+        return (one.clone(), one)
+    }
+    let (lo, hi) = expm1_pos_bounds(&r.clone().abs(), prec);
+    let (lo, hi) = (Float::add_to(&one, &lo, prec, DOWN), Float::add_to(&one, &hi, prec, UP));
+    match r.sign {
+        Sign::Pos => (lo, hi),
+        Sign::Neg => (Float::div_to(&one, &hi, prec, DOWN), Float::div_to(&one, &lo, prec, UP)),
+    }
+}
+
+// bounds on a value between 1 and `1 + 2**-(prec + 1)` (or the same
+// below 1, if `sign` is negative): there are no values of precision
+// `prec + 1` strictly between them, so everything there rounds the
+// same to `prec` bits.
+pub fn near_one_bounds(sign: Sign, prec: u32) -> (Float, Float) {
+    let one = Float::from(1);
+    let eps = one.clone().mul_exp2(-(prec as i64) - 1);
+    match sign {
+        Sign::Pos => (one.clone(), Float::add_to(&one, &eps, prec + 2, UP)),
+        Sign::Neg => (Float::sub_to(&one, &eps, prec + 2, DOWN), one),
+    }
+}
+
+// the exponent of the lowest set bit of a normal `x`, so `x` is an
+// integer if this is non-negative, and an odd one if it is zero.
+pub fn lowest_bit(x: &Float) -> i64 {
+    x.exp.saturating_sub(x.prec as i64 - 1 - x.signif.trailing_zeros() as i64)
+}
+
+// `x == n + f` where `n` is the nearest integer to `x` and `|f| <=
+// 1/2`, if `n` fits in an `i64`, for a normal `x` with `|x| < 2**63`.
+pub fn split_integer(x: &Float) -> Option<(i64, Float)> {
+    if x.exp < -1 {
+        return Some((0, x.clone()))
+    }
+    let shift = x.prec as i64 - 1 - x.exp;
+    let mut n = if shift <= 0 {
+        &x.signif << -shift as usize
+    } else {
+        (&x.signif + &(Int::from(1) << (shift - 1) as usize)) >> shift as usize
+    };
+    if x.sign == Sign::Neg {
+        n.negate();
+    }
+    if Int::from(i64::MIN) <= n && n <= Int::from(i64::MAX) {
+        let n = i64::from(&n);
+        // this is exact, since `f` has no bits below those of `x`
+        // and is smaller than it.
+        let f = Float::sub_to(x, &Float::from(n), x.prec, RoundingMode::NearestEven);
+        Some((n, f))
+    } else {
+        None
+    }
+}
+
+// `2**n` rounded to `prec` bits according to `mode`, which is exact
+// (like `mul_exp2`) unless it is outside the exponent range.
+fn power_of_two(n: i64, prec: u32, mode: RoundingMode) -> (Float, Ordering) {
+    let mut x = Float {
+        prec: prec,
+        sign: Sign::Pos,
+        exp: 0,
+        signif: Int::from(1) << (prec as usize - 1),
+        style: Style::Normal,
+    };
+    let ord = x.normalise_at((n, false), false, mode);
+    (x, ord)
+}
+
+// round a value beyond the range of exponents, i.e. too large if
+// `big` and otherwise too small, with sign `sign`.
+pub fn out_of_range(prec: u32, sign: Sign, big: bool, mode: RoundingMode) -> (Float, Ordering) {
+    let mut x = Float {
+        prec: prec,
+        sign: sign,
+        exp: 0,
+        signif: Int::from(1) << (prec as usize - 1),
+        style: Style::Normal,
+    };
+    let exp = if big { (i64::MAX, true) } else { (i64::MIN, true) };
+    let ord = x.normalise_at(exp, false, mode);
+    (x, ord)
+}
+
+// compute `e**x` rounded to `prec` bits, storing the result in `x`
+// and returning how it compares to the exact value.
+fn exp_in_place(x: &mut Float, prec: u32, mode: RoundingMode) -> Ordering {
+    x.debug_assert_valid();
+
+    match x.style {
+        Style::NaN => *x = Float::nan(prec),
+        Style::Infinity => {
+            *x = match x.sign {
+                Sign::Pos => Float::inf(prec, Sign::Pos),
+                Sign::Neg => Float::zero_(prec, Sign::Pos),
+            }
+        }
+        Style::Zero => {
+            let (one, ord) = Float::from(1).with_precision_ternary(prec, mode);
+            *x = one;
+            return ord
+        }
+        Style::Normal => {
+            // e**x == e**r * 2**k, where `r` is small.
+            let k = if x.exp < 63 { div_ln2(x) } else { None };
+            let (result, ord) = match k {
+                // e**x is between 1 and 1 + 2x, so it rounds like
+                // anything else there. Using the series instead would
+                // be slow, or never finish, for the tiniest x.
+                _ if x.exp < -(prec as i64) - 2 => {
+                    round_bounds(Sign::Pos, 0, prec, mode, |_| near_one_bounds(x.sign, prec))
+                }
+                Some(k) => {
+                    round_bounds(Sign::Pos, k, prec, mode, |working| {
+                        let (r_lo, r_hi) = sub_ln2_bounds(x, k, working);
+                        (exp_bounds(&r_lo, working).0, exp_bounds(&r_hi, working).1)
+                    })
+                }
+                // |x / ln(2)| >= 2**63, so e**x is far outside the
+                // range.
+                None => out_of_range(prec, Sign::Pos, x.sign == Sign::Pos, mode),
+            };
+            *x = result;
+            return ord
+        }
+    }
+    Ordering::Equal
+}
+
+// compute `e**x - 1` rounded to `prec` bits, storing the result in
+// `x` and returning how it compares to the exact value.
+fn expm1_in_place(x: &mut Float, prec: u32, mode: RoundingMode) -> Ordering {
+    x.debug_assert_valid();
+
+    match x.style {
+        Style::NaN => *x = Float::nan(prec),
+        Style::Infinity => {
+            if let Sign::Neg = x.sign {
+                let (minus_one, ord) = Float::from(-1).with_precision_ternary(prec, mode);
+                *x = minus_one;
+                return ord
+            }
+            *x = Float::inf(prec, Sign::Pos)
+        }
+        Style::Zero => *x = Float::zero_(prec, x.sign),
+        Style::Normal => {
+            let one = Float::from(1);
+            let k = if x.exp < 63 { div_ln2(x) } else { None };
+            let bits = cmp::max(x.prec, prec);
+            let (result, ord) = if x.exp < -(bits as i64) - 2 {
+                // e**x - 1 is between x and x + x**2, which is less
+                // than one unit in the last place of `x` with one more
+                // bit than `bits`, so nothing rounds differently
+                // between them. This is scaled to avoid the bottom of
+                // the exponent range.
+                let mut y = x.clone().abs().with_precision(bits + 1);
+                y.exp = 0;
+                round_bounds(x.sign, x.exp, prec, mode, |_| {
+                    match x.sign {
+                        Sign::Pos => (y.clone(), y.clone().next_above()),
+                        Sign::Neg => (y.clone().next_below(), y.clone()),
+                    }
+                })
+            } else if x.exp < 0 {
+                // |x| < 1, so the series is used directly, to avoid
+                // cancellation. For negative x, |e**x - 1| ==
+                // (e**|x| - 1) / e**|x|, which is also below |x|: the
+                // bound computed from the series can be above |x| for
+                // tiny x, and would never round the same as the lower
+                // one if it is a representable value.
+                let abs = x.clone().abs();
+                round_bounds(x.sign, 0, prec, mode, |working| {
+                    let (lo, hi) = expm1_pos_bounds(&abs, working);
+                    match x.sign {
+                        Sign::Pos => (lo, hi),
+                        Sign::Neg => {
+                            let lo_exp = Float::add_to(&one, &hi, working, UP);
+                            let hi_exp = Float::add_to(&one, &lo, working, DOWN);
+                            let hi = Float::div_to(&hi, &hi_exp, working, UP);
+                            (Float::div_to(&lo, &lo_exp, working, DOWN),
+                             if hi < abs { hi } else { abs.clone() })
+                        }
+                    }
+                })
+            } else if x.sign == Sign::Pos {
+                match k {
+                    // e**x - 1 == (e**r - 2**-k) * 2**k
+                    Some(k) => {
+                        round_bounds(Sign::Pos, k, prec, mode, |working| {
+                            let power = one.clone().mul_exp2(-k);
+                            let (r_lo, r_hi) = sub_ln2_bounds(x, k, working);
+                            (Float::sub_to(&exp_bounds(&r_lo, working).0, &power, working, DOWN),
+                             Float::sub_to(&exp_bounds(&r_hi, working).1, &power, working, UP))
+                        })
+                    }
+                    None => out_of_range(prec, Sign::Pos, true, mode),
+                }
+            } else {
+                // x <= -1, so e**x - 1 == -(1 - e**r * 2**k) is in
+                // (-1, -0.6].
+                round_bounds(Sign::Neg, 0, prec, mode, |working| {
+                    match k {
+                        Some(k) if k >= -(working as i64) - 8 => {
+                            let (r_lo, r_hi) = sub_ln2_bounds(x, k, working);
+                            let (lo, hi) = (exp_bounds(&r_lo, working).0,
+                                            exp_bounds(&r_hi, working).1);
+                            (Float::sub_to(&one, &hi.mul_exp2(k), working, DOWN),
+                             Float::sub_to(&one, &lo.mul_exp2(k), working, UP))
+                        }
+                        // e**x < 2**(k + 3), which is negligible.
+                        _ => {
+                            let tiny = one.clone().mul_exp2(-(working as i64) - 5);
+                            (Float::sub_to(&one, &tiny, working + 8, DOWN), one.clone())
+                        }
+                    }
+                })
+            };
+            *x = result;
+            return ord
+        }
+    }
+    Ordering::Equal
+}
+
+// compute `2**x` rounded to `prec` bits, storing the result in `x`
+// and returning how it compares to the exact value.
+fn exp2_in_place(x: &mut Float, prec: u32, mode: RoundingMode) -> Ordering {
+    x.debug_assert_valid();
+
+    match x.style {
+        Style::NaN => *x = Float::nan(prec),
+        Style::Infinity => {
+            *x = match x.sign {
+                Sign::Pos => Float::inf(prec, Sign::Pos),
+                Sign::Neg => Float::zero_(prec, Sign::Pos),
+            }
+        }
+        Style::Zero => {
+            let (one, ord) = Float::from(1).with_precision_ternary(prec, mode);
+            *x = one;
+            return ord
+        }
+        Style::Normal => {
+            // 2**x == 2**f * 2**n, for an integer `n` and small `f`.
+            let parts = if x.exp < 63 { split_integer(x) } else { None };
+            let (result, ord) = match parts {
+                Some((n, ref f)) => {
+                    match f.style {
+                        Style::Zero => power_of_two(n, prec, mode),
+                        // as for `e**f`, 2**f is very close to 1.
+                        _ if f.exp < -(prec as i64) - 2 => {
+                            round_bounds(Sign::Pos, n, prec, mode, |_| near_one_bounds(f.sign, prec))
+                        }
+                        // 2**f == e**(f ln(2))
+                        _ => {
+                            round_bounds(Sign::Pos, n, prec, mode, |working| {
+                                let (ln2_lo, ln2_hi) = ln2_bounds(working);
+                                let (small, big) = match f.sign {
+                                    Sign::Pos => (ln2_lo, ln2_hi),
+                                    Sign::Neg => (ln2_hi, ln2_lo),
+                                };
+                                (exp_bounds(&Float::mul_to(f, &small, working, DOWN), working).0,
+                                 exp_bounds(&Float::mul_to(f, &big, working, UP), working).1)
+                            })
+                        }
+                    }
+                }
+                // |x| >= 2**63, so 2**x is far outside the range.
+                None => out_of_range(prec, Sign::Pos, x.sign == Sign::Pos, mode),
+            };
+            *x = result;
+            return ord
+        }
+    }
+    Ordering::Equal
+}
+
+// compute `10**x` rounded to `prec` bits, storing the result in `x`
+// and returning how it compares to the exact value.
+fn exp10_in_place(x: &mut Float, prec: u32, mode: RoundingMode) -> Ordering {
+    x.debug_assert_valid();
+
+    match x.style {
+        Style::NaN => *x = Float::nan(prec),
+        Style::Infinity => {
+            *x = match x.sign {
+                Sign::Pos => Float::inf(prec, Sign::Pos),
+                Sign::Neg => Float::zero_(prec, Sign::Pos),
+            }
+        }
+        Style::Zero => {
+            let (one, ord) = Float::from(1).with_precision_ternary(prec, mode);
+            *x = one;
+            return ord
+        }
+        Style::Normal => {
+            // 10**x == e**(x ln(10)) == e**r * 2**k, where `r` is
+            // small.
+            let k = if x.exp < 63 {
+                let y = env::quietly(|| {
+                    Float::mul_to(x, &ln10_bounds(128).0, 128, RoundingMode::NearestEven)
+                });
+                if y.exp < 63 { div_ln2(&y) } else { None }
+            } else {
+                None
+            };
+            let (result, ord) = match k {
+                // as for `e**x`, 10**x is very close to 1.
+                _ if x.exp < -(prec as i64) - 4 => {
+                    round_bounds(Sign::Pos, 0, prec, mode, |_| near_one_bounds(x.sign, prec))
+                }
+                // 10**n == 5**n * 2**n for an integer `n`, which is
+                // in range since `k` is.
+                Some(_) if lowest_bit(x) >= 0 => {
+                    let n = split_integer(x).unwrap().0;
+                    if 0 <= n && n <= prec as i64 {
+                        // small enough to compute exactly, and round
+                        // once.
+                        let signif = Int::from(5).pow(n as usize);
+                        let high = signif.bit_length() as i64 - 1;
+                        let mut result = Float {
+                            prec: prec,
+                            sign: Sign::Pos,
+                            exp: 0,
+                            signif: signif,
+                            style: Style::Normal,
+                        };
+                        let ord = result.normalise_at(exp_sum(high, n, 0), false, mode);
+                        (result, ord)
+                    } else if n > 0 {
+                        // 5**n is odd with more than `prec + 1` bits,
+                        // so it is not representable, or halfway
+                        // between two representable values.
+                        round_bounds(Sign::Pos, n, prec, mode, |working| {
+                            pow_bounds(5, n as u64, working)
+                        })
+                    } else {
+                        // 1 / 5**-n is never representable.
+                        let one = Float::from(1);
+                        round_bounds(Sign::Pos, n, prec, mode, |working| {
+                            let (lo, hi) = pow_bounds(5, n.wrapping_neg() as u64, working);
+                            (Float::div_to(&one, &hi, working, DOWN),
+                             Float::div_to(&one, &lo, working, UP))
+                        })
+                    }
+                }
+                Some(k) => {
+                    round_bounds(Sign::Pos, k, prec, mode, |working| {
+                        // x ln(10) may be large, and needs to be
+                        // accurate in absolute terms.
+                        let work = working + cmp::max(x.exp, 0) as u32 + 8;
+                        let (ln10_lo, ln10_hi) = ln10_bounds(work);
+                        let (small, big) = match x.sign {
+                            Sign::Pos => (ln10_lo, ln10_hi),
+                            Sign::Neg => (ln10_hi, ln10_lo),
+                        };
+                        let y_lo = Float::mul_to(x, &small, work, DOWN);
+                        let y_hi = Float::mul_to(x, &big, work, UP);
+                        (exp_bounds(&sub_ln2_bounds(&y_lo, k, working).0, working).0,
+                         exp_bounds(&sub_ln2_bounds(&y_hi, k, working).1, working).1)
+                    })
+                }
+                // |x log2(10)| >= 2**63, so 10**x is far outside the
+                // range.
+                None => out_of_range(prec, Sign::Pos, x.sign == Sign::Pos, mode),
+            };
+            *x = result;
+            return ord
+        }
+    }
+    Ordering::Equal
+}
@@ -0,0 +1,220 @@
+use {Style, Float, RoundingMode};
+use super::addsub::{Operation, Exact, add_exact, exact_zero_sign};
+use ramp::Int;
+
+use std::cmp;
+use std::cmp::Ordering;
+
+// Like the binary operations, the result has the largest of the
+// precisions, and is rounded once from the exact value.
+impl Float {
+    /// Compute `self * a + b` with a single rounding, to nearest (like
+    /// `f64::mul_add`).
+    pub fn mul_add(self, a: &Float, b: &Float) -> Float {
+        self.mul_add_round(a, b, RoundingMode::NearestEven)
+    }
+    /// Compute `self * a + b` with a single rounding, according to
+    /// `mode`.
+    pub fn mul_add_round(self, a: &Float, b: &Float, mode: RoundingMode) -> Float {
+        self.mul_add_ternary(a, b, mode).0
+    }
+    /// Compute `self * a + b` with a single rounding, according to
+    /// `mode`, and returning how the result compares to the exact
+    /// value.
+    pub fn mul_add_ternary(mut self, a: &Float, b: &Float,
+                           mode: RoundingMode) -> (Float, Ordering) {
+        let prec = cmp::max(cmp::max(self.prec, a.prec), b.prec);
+        let ord = mul_add_in_place(&mut self, a, b, prec, mode);
+        (self, ord)
+    }
+    /// Compute `x * y + z` rounded directly to `prec` bits according
+    /// to `mode`, independent of the precisions of `x`, `y` and `z`.
+    pub fn mul_add_to(x: &Float, y: &Float, z: &Float, prec: u32, mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut x = x.clone();
+        mul_add_in_place(&mut x, y, z, prec, mode);
+        x
+    }
+
+    /// Compute `self * b + c * d` with a single rounding, to nearest.
+    pub fn fmma(self, b: &Float, c: &Float, d: &Float) -> Float {
+        self.fmma_round(b, c, d, RoundingMode::NearestEven)
+    }
+    /// Compute `self * b - c * d` with a single rounding, to nearest.
+    pub fn fmms(self, b: &Float, c: &Float, d: &Float) -> Float {
+        self.fmms_round(b, c, d, RoundingMode::NearestEven)
+    }
+    /// Compute `self * b + c * d` with a single rounding, according
+    /// to `mode`.
+    pub fn fmma_round(self, b: &Float, c: &Float, d: &Float, mode: RoundingMode) -> Float {
+        self.fmma_ternary(b, c, d, mode).0
+    }
+    /// Compute `self * b - c * d` with a single rounding, according
+    /// to `mode`.
+    pub fn fmms_round(self, b: &Float, c: &Float, d: &Float, mode: RoundingMode) -> Float {
+        self.fmms_ternary(b, c, d, mode).0
+    }
+    /// Compute `self * b + c * d` with a single rounding, according
+    /// to `mode`, and returning how the result compares to the exact
+    /// value.
+    pub fn fmma_ternary(mut self, b: &Float, c: &Float, d: &Float,
+                        mode: RoundingMode) -> (Float, Ordering) {
+        let prec = cmp::max(cmp::max(self.prec, b.prec), cmp::max(c.prec, d.prec));
+        let ord = fmma_in_place(&mut self, b, c, d, Operation::Add, prec, mode);
+        (self, ord)
+    }
+    /// Compute `self * b - c * d` with a single rounding, according
+    /// to `mode`, and returning how the result compares to the exact
+    /// value.
+    pub fn fmms_ternary(mut self, b: &Float, c: &Float, d: &Float,
+                        mode: RoundingMode) -> (Float, Ordering) {
+        let prec = cmp::max(cmp::max(self.prec, b.prec), cmp::max(c.prec, d.prec));
+        let ord = fmma_in_place(&mut self, b, c, d, Operation::Sub, prec, mode);
+        (self, ord)
+    }
+    /// Compute `a * b + c * d` rounded directly to `prec` bits
+    /// according to `mode`, independent of the precisions of the
+    /// arguments.
+    pub fn fmma_to(a: &Float, b: &Float, c: &Float, d: &Float, prec: u32,
+                   mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut a = a.clone();
+        fmma_in_place(&mut a, b, c, d, Operation::Add, prec, mode);
+        a
+    }
+    /// Compute `a * b - c * d` rounded directly to `prec` bits
+    /// according to `mode`, independent of the precisions of the
+    /// arguments.
+    pub fn fmms_to(a: &Float, b: &Float, c: &Float, d: &Float, prec: u32,
+                   mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut a = a.clone();
+        fmma_in_place(&mut a, b, c, d, Operation::Sub, prec, mode);
+        a
+    }
+}
+
+impl Exact {
+    fn product(x: &Float, y: &Float) -> Exact {
+        Exact {
+            sign: x.sign ^ y.sign,
+            signif: &x.signif * &y.signif,
+            low: Int::from(x.exp) + Int::from(y.exp) - Int::from(x.prec + y.prec - 2),
+        }
+    }
+}
+
+// the style of the product of values with styles `x` and `y`, where
+// `0.0 * inf` is NaN.
+fn product_style(x: Style, y: Style) -> Style {
+    match (x, y) {
+        (Style::NaN, _) | (_, Style::NaN) => Style::NaN,
+        (Style::Infinity, Style::Zero) | (Style::Zero, Style::Infinity) => Style::NaN,
+        (Style::Infinity, _) | (_, Style::Infinity) => Style::Infinity,
+        (Style::Zero, _) | (_, Style::Zero) => Style::Zero,
+        (Style::Normal, Style::Normal) => Style::Normal,
+    }
+}
+
+// compute `x * y + z` rounded to `prec` bits, storing the result in
+// `x` and returning how it compares to the exact value.
+fn mul_add_in_place(x: &mut Float, y: &Float, z: &Float, prec: u32,
+                    mode: RoundingMode) -> Ordering {
+    x.debug_assert_valid();
+    y.debug_assert_valid();
+    z.debug_assert_valid();
+
+    let sign = x.sign ^ y.sign;
+    match (product_style(x.style, y.style), z.style) {
+        (_, Style::NaN) => *x = Float::nan(prec),
+        (Style::NaN, _) => {
+            // 0.0 * inf, inf * 0.0 are NaN, whatever is added to them
+            let is_nan = match (x.style, y.style) {
+                (Style::NaN, _) | (_, Style::NaN) => true,
+                _ => false,
+            };
+            *x = if is_nan { Float::nan(prec) } else { Float::invalid(prec) }
+        }
+        (Style::Infinity, Style::Infinity) => {
+            *x = if sign == z.sign { Float::inf(prec, sign) } else { Float::invalid(prec) }
+        }
+        (Style::Infinity, _) => *x = Float::inf(prec, sign),
+        (_, Style::Infinity) => *x = Float::inf(prec, z.sign),
+        (Style::Zero, Style::Zero) => {
+            let sign = if sign == z.sign { sign } else { exact_zero_sign(mode) };
+            *x = Float::zero_(prec, sign)
+        }
+        (Style::Zero, Style::Normal) => {
+            x.clone_from(z);
+            x.prec = prec;
+            return x.normalise(false, mode)
+        }
+        (Style::Normal, Style::Zero) => {
+            let (result, ord) = Exact::product(x, y).round(prec, mode);
+            *x = result;
+            return ord
+        }
+        (Style::Normal, Style::Normal) => {
+            let (result, ord) = add_exact(Exact::product(x, y), Exact::new(z), prec, mode);
+            *x = result;
+            return ord
+        }
+    }
+    Ordering::Equal
+}
+
+// compute `x * y + z * w` or `x * y - z * w` (depending on `op`),
+// rounded to `prec` bits, storing the result in `x` and returning
+// how it compares to the exact value.
+fn fmma_in_place(x: &mut Float, y: &Float, z: &Float, w: &Float, op: Operation,
+                 prec: u32, mode: RoundingMode) -> Ordering {
+    x.debug_assert_valid();
+    y.debug_assert_valid();
+    z.debug_assert_valid();
+    w.debug_assert_valid();
+
+    let xy_sign = x.sign ^ y.sign;
+    let zw_sign = match op {
+        Operation::Add => z.sign ^ w.sign,
+        Operation::Sub => -(z.sign ^ w.sign),
+    };
+    match (product_style(x.style, y.style), product_style(z.style, w.style)) {
+        (Style::NaN, _) | (_, Style::NaN) => {
+            let is_nan = match (x.style, y.style, z.style, w.style) {
+                (Style::NaN, _, _, _) | (_, Style::NaN, _, _) |
+                (_, _, Style::NaN, _) | (_, _, _, Style::NaN) => true,
+                _ => false,
+            };
+            *x = if is_nan { Float::nan(prec) } else { Float::invalid(prec) }
+        }
+        (Style::Infinity, Style::Infinity) => {
+            *x = if xy_sign == zw_sign { Float::inf(prec, xy_sign) } else { Float::invalid(prec) }
+        }
+        (Style::Infinity, _) => *x = Float::inf(prec, xy_sign),
+        (_, Style::Infinity) => *x = Float::inf(prec, zw_sign),
+        (Style::Zero, Style::Zero) => {
+            let sign = if xy_sign == zw_sign { xy_sign } else { exact_zero_sign(mode) };
+            *x = Float::zero_(prec, sign)
+        }
+        (Style::Zero, Style::Normal) => {
+            let mut product = Exact::product(z, w);
+            product.sign = zw_sign;
+            let (result, ord) = product.round(prec, mode);
+            *x = result;
+            return ord
+        }
+        (Style::Normal, Style::Zero) => {
+            let (result, ord) = Exact::product(x, y).round(prec, mode);
+            *x = result;
+            return ord
+        }
+        (Style::Normal, Style::Normal) => {
+            let mut zw = Exact::product(z, w);
+            zw.sign = zw_sign;
+            let (result, ord) = add_exact(Exact::product(x, y), zw, prec, mode);
+            *x = result;
+            return ord
+        }
+    }
+    Ordering::Equal
+}
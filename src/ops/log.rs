@@ -0,0 +1,408 @@
+use {Style, Sign, Float, RoundingMode, Exception};
+use env;
+use super::exp::{round_bounds, ln2_bounds, lowest_bit};
+use ramp::Int;
+
+use std::cmp;
+use std::cmp::Ordering;
+
+impl Float {
+    /// Compute the natural logarithm of `self`, rounding to nearest.
+    pub fn ln(self) -> Float {
+        self.ln_round(RoundingMode::NearestEven)
+    }
+    /// Compute the natural logarithm of `self`, rounding according
+    /// to `mode`.
+    pub fn ln_round(self, mode: RoundingMode) -> Float {
+        self.ln_ternary(mode).0
+    }
+    /// Compute the natural logarithm of `self`, rounding according
+    /// to `mode`, and returning how the result compares to the exact
+    /// value.
+    pub fn ln_ternary(mut self, mode: RoundingMode) -> (Float, Ordering) {
+        let prec = self.prec;
+        let ord = log_in_place(&mut self, Base::E, prec, mode);
+        (self, ord)
+    }
+    /// Compute the natural logarithm of `x` rounded directly to
+    /// `prec` bits according to `mode`, independent of the precision
+    /// of `x`.
+    pub fn ln_to(x: &Float, prec: u32, mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut x = x.clone();
+        log_in_place(&mut x, Base::E, prec, mode);
+        x
+    }
+
+    /// Compute `ln(1 + self)`, rounding to nearest. Unlike
+    /// `(self + 1).ln()`, this is accurate when `self` is near zero.
+    pub fn ln_1p(self) -> Float {
+        self.ln_1p_round(RoundingMode::NearestEven)
+    }
+    /// Compute `ln(1 + self)`, rounding according to `mode`.
+    pub fn ln_1p_round(self, mode: RoundingMode) -> Float {
+        self.ln_1p_ternary(mode).0
+    }
+    /// Compute `ln(1 + self)`, rounding according to `mode`, and
+    /// returning how the result compares to the exact value.
+    pub fn ln_1p_ternary(mut self, mode: RoundingMode) -> (Float, Ordering) {
+        let prec = self.prec;
+        let ord = ln_1p_in_place(&mut self, prec, mode);
+        (self, ord)
+    }
+    /// Compute `ln(1 + x)` rounded directly to `prec` bits according
+    /// to `mode`, independent of the precision of `x`.
+    pub fn ln_1p_to(x: &Float, prec: u32, mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut x = x.clone();
+        ln_1p_in_place(&mut x, prec, mode);
+        x
+    }
+
+    /// Compute the base 2 logarithm of `self`, rounding to nearest.
+    /// This is exact if `self` is a power of two.
+    pub fn log2(self) -> Float {
+        self.log2_round(RoundingMode::NearestEven)
+    }
+    /// Compute the base 2 logarithm of `self`, rounding according
+    /// to `mode`.
+    pub fn log2_round(self, mode: RoundingMode) -> Float {
+        self.log2_ternary(mode).0
+    }
+    /// Compute the base 2 logarithm of `self`, rounding according
+    /// to `mode`, and returning how the result compares to the exact
+    /// value.
+    pub fn log2_ternary(mut self, mode: RoundingMode) -> (Float, Ordering) {
+        let prec = self.prec;
+        let ord = log_in_place(&mut self, Base::Two, prec, mode);
+        (self, ord)
+    }
+    /// Compute the base 2 logarithm of `x` rounded directly to
+    /// `prec` bits according to `mode`, independent of the precision
+    /// of `x`.
+    pub fn log2_to(x: &Float, prec: u32, mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut x = x.clone();
+        log_in_place(&mut x, Base::Two, prec, mode);
+        x
+    }
+
+    /// Compute the base 10 logarithm of `self`, rounding to nearest.
+    /// This is exact if `self` is a power of ten.
+    pub fn log10(self) -> Float {
+        self.log10_round(RoundingMode::NearestEven)
+    }
+    /// Compute the base 10 logarithm of `self`, rounding according
+    /// to `mode`.
+    pub fn log10_round(self, mode: RoundingMode) -> Float {
+        self.log10_ternary(mode).0
+    }
+    /// Compute the base 10 logarithm of `self`, rounding according
+    /// to `mode`, and returning how the result compares to the exact
+    /// value.
+    pub fn log10_ternary(mut self, mode: RoundingMode) -> (Float, Ordering) {
+        let prec = self.prec;
+        let ord = log_in_place(&mut self, Base::Ten, prec, mode);
+        (self, ord)
+    }
+    /// Compute the base 10 logarithm of `x` rounded directly to
+    /// `prec` bits according to `mode`, independent of the precision
+    /// of `x`.
+    pub fn log10_to(x: &Float, prec: u32, mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut x = x.clone();
+        log_in_place(&mut x, Base::Ten, prec, mode);
+        x
+    }
+}
+
+const DOWN: RoundingMode = RoundingMode::TowardNegative;
+const UP: RoundingMode = RoundingMode::TowardPositive;
+
+// `atanh(z)` rounded to about `prec` bits in the direction of `mode`
+// (`DOWN` or `UP`), for `0 < z <= 1/3`, using
+//
+//     atanh(z) == sum(z**(2k + 1) / (2k + 1))
+fn atanh_pos(z: &Float, prec: u32, mode: RoundingMode) -> Float {
+    let z2 = Float::mul_to(z, z, prec, mode);
+    let mut sum = z.clone();
+    let mut power = z.clone();
+    let mut n = 1u32;
+    loop {
+        n += 2;
+        power = Float::mul_to(&power, &z2, prec, mode);
+        let term = Float::div_to(&power, &Float::from(n), prec, mode);
+        sum = Float::add_to(&sum, &term, prec, mode);
+        if term.exp < sum.exp.saturating_sub(prec as i64) {
+            // `z**2 <= 1/9`, so the rest of the series sums to less
+            // than the last term.
+            return if mode == UP { Float::add_to(&sum, &term, prec, UP) } else { sum }
+        }
+    }
+}
+
+// `atanh(z)` rounded to about `prec` bits in the direction of `mode`,
+// for `|z| <= 1/3`.
+fn atanh_directed(z: &Float, prec: u32, mode: RoundingMode) -> Float {
+    if let Style::Zero = z.style {
+        return z.clone()
+    }
+    match z.sign {
+        Sign::Pos => atanh_pos(z, prec, mode),
+        Sign::Neg => {
+            let opposite = if mode == UP { DOWN } else { UP };
+            -atanh_pos(&z.clone().abs(), prec, opposite)
+        }
+    }
+}
+
+// `ln(1 + t)` rounded down and up to about `prec` bits, for a normal
+// `t` with `-1/2 <= t <= 1`.
+fn ln1p_bounds(t: &Float, prec: u32) -> (Float, Float) {
+    // ln(1 + t) == 2**j ln(1 + u), where 1 + u == (1 + t)**(2**-j),
+    // which is computed with `j` square roots, chosen so that `u` is
+    // small and the series for
+    //
+    //     ln(1 + u) == 2 atanh(u / (2 + u))
+    //
+    // converges quickly. Subtracting 1 from the roots is exact, but
+    // loses about `j` bits of relative precision.
+    let m = cmp::max((prec as f64).sqrt() as i64, 2);
+    let j = cmp::max(t.exp.saturating_add(m), 0);
+    let work = prec + 2 * j as u32 + 32;
+    let (u_lo, u_hi) = if j == 0 {
+        (t.clone(), t.clone())
+    } else {
+        let one = Float::from(1);
+        // this is exact, since `t.exp >= -m`.
+        let y = Float::add_to(&one, t, t.prec + m as u32 + 2, RoundingMode::NearestEven);
+        let (mut lo, mut hi) = (y.clone(), y);
+        for _ in 0..j {
+            lo = Float::sqrt_to(&lo, work, DOWN);
+            hi = Float::sqrt_to(&hi, work, UP);
+        }
+        (Float::sub_to(&lo, &one, work, DOWN), Float::sub_to(&hi, &one, work, UP))
+    };
+
+    // `u / (2 + u)` increases with `u`, so each bound on it comes
+    // from the bound on `u` in the same direction, with the
+    // denominator rounded the opposite way for positive `u`, and the
+    // same way for negative `u`.
+    let two = Float::from(2);
+    let quotient = |u: &Float, mode: RoundingMode| {
+        let opposite = if mode == UP { DOWN } else { UP };
+        let denominator_mode = if u.sign == Sign::Pos { opposite } else { mode };
+        Float::div_to(u, &Float::add_to(&two, u, work, denominator_mode), work, mode)
+    };
+    let (z_lo, z_hi) = (quotient(&u_lo, DOWN), quotient(&u_hi, UP));
+    (atanh_directed(&z_lo, work, DOWN).mul_exp2(j + 1),
+     atanh_directed(&z_hi, work, UP).mul_exp2(j + 1))
+}
+
+// `x == (1 + t) * 2**e` with `-1/4 <= t < 1/2` exactly, for a normal
+// `x > 0`.
+fn split(x: &Float) -> (Float, i64) {
+    let mut m = x.clone();
+    m.exp = 0;
+    let mut e = x.exp;
+    if m >= 1.5 {
+        m.exp = -1;
+        e += 1;
+    }
+    // `1/2 <= m <= 2`, so this is exact, but `m` and `t` may be
+    // outside the caller's exponent range.
+    let t = env::quietly(|| Float::sub_to(&m, &Float::from(1), m.prec, RoundingMode::NearestEven));
+    (t, e)
+}
+
+// `ln(x)` rounded down and up to about `prec` bits, for a normal
+// `x > 0` with `x != 1`.
+pub fn ln_bounds(x: &Float, prec: u32) -> (Float, Float) {
+    let (t, e) = split(x);
+    let (lo, hi) = match t.style {
+        Style::Zero => (t.clone(), t),
+        _ => ln1p_bounds(&t, prec),
+    };
+    if e == 0 {
+        return (lo, hi)
+    }
+    // ln(x) == ln(1 + t) + e ln(2), where the first term is at most
+    // 0.41 in magnitude and so the second dominates. The products
+    // are exact, so all of the error comes from `ln(2)`, which needs
+    // more bits the larger `e` is.
+    let e_bits = 64 - e.wrapping_abs().leading_zeros();
+    let (ln2_lo, ln2_hi) = ln2_bounds(prec + e_bits + 8);
+    let (small, big) = if e > 0 { (ln2_lo, ln2_hi) } else { (ln2_hi, ln2_lo) };
+    let e = Float::from(e);
+    let work = prec + 2 * e_bits + 8;
+    let nearest = RoundingMode::NearestEven;
+    (Float::add_to(&lo, &Float::mul_to(&e, &small, work, nearest), prec, DOWN),
+     Float::add_to(&hi, &Float::mul_to(&e, &big, work, nearest), prec, UP))
+}
+
+// the magnitudes of the bounds `lo` and `hi` on a value with sign
+// `sign`, smallest first.
+fn magnitudes(sign: Sign, (lo, hi): (Float, Float)) -> (Float, Float) {
+    match sign {
+        Sign::Pos => (lo, hi),
+        Sign::Neg => (-hi, -lo),
+    }
+}
+
+#[derive(Copy, Clone)]
+enum Base {
+    E,
+    Two,
+    Ten,
+}
+
+// the logarithm of `x` in base `base`, if it is an integer, which
+// happens exactly when `x` is an integer power of the base.
+fn exact_log(x: &Float, base: Base) -> Option<i64> {
+    match base {
+        Base::E => {
+            let (t, e) = split(x);
+            match (t.style, e) {
+                (Style::Zero, 0) => Some(0),
+                _ => None,
+            }
+        }
+        Base::Two => {
+            if x.signif.trailing_zeros() == x.prec - 1 { Some(x.exp) } else { None }
+        }
+        Base::Ten => {
+            // 10**n == 5**n * 2**n, which isn't representable for n <
+            // 0, and 5**n has more than 2n bits.
+            let n = lowest_bit(x);
+            let odd = &x.signif >> x.signif.trailing_zeros() as usize;
+            if 0 <= n && n < (odd.bit_length() as i64 + 1) / 2 && odd == Int::from(5).pow(n as usize) {
+                Some(n)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+// `ln(10)` rounded down and up to about `prec` bits.
+pub fn ln10_bounds(prec: u32) -> (Float, Float) {
+    ln_bounds(&Float::from(10), prec)
+}
+
+// bounds on `y / d` rounded down and up to `prec` bits, given bounds
+// on `y` and on the positive `d`.
+fn div_bounds((lo, hi): (Float, Float), (d_lo, d_hi): (Float, Float),
+              prec: u32) -> (Float, Float) {
+    (Float::div_to(&lo, if lo.sign == Sign::Pos { &d_hi } else { &d_lo }, prec, DOWN),
+     Float::div_to(&hi, if hi.sign == Sign::Pos { &d_lo } else { &d_hi }, prec, UP))
+}
+
+// compute the logarithm of `x` in base `base`, rounded to `prec`
+// bits, storing the result in `x` and returning how it compares to
+// the exact value.
+fn log_in_place(x: &mut Float, base: Base, prec: u32, mode: RoundingMode) -> Ordering {
+    x.debug_assert_valid();
+
+    match x.style {
+        Style::NaN => *x = Float::nan(prec),
+        Style::Infinity => {
+            *x = match x.sign {
+                Sign::Pos => Float::inf(prec, Sign::Pos),
+                Sign::Neg => Float::invalid(prec),
+            }
+        }
+        // log(0) == -inf, exactly, like division by zero
+        Style::Zero => {
+            env::raise(Exception::DivideByZero);
+            *x = Float::inf(prec, Sign::Neg)
+        }
+        Style::Normal => {
+            if x.sign == Sign::Neg {
+                *x = Float::invalid(prec);
+                return Ordering::Equal
+            }
+            // every other logarithm is irrational.
+            if let Some(n) = exact_log(x, base) {
+                let (result, ord) = Float::from(n).with_precision_ternary(prec, mode);
+                *x = result;
+                return ord
+            }
+            let (t, e) = split(x);
+            let sign = if e > 0 || (e == 0 && t.sign == Sign::Pos) { Sign::Pos } else { Sign::Neg };
+            let (result, ord) = round_bounds(sign, 0, prec, mode, |working| {
+                let bounds = ln_bounds(x, working);
+                magnitudes(sign, match base {
+                    Base::E => bounds,
+                    Base::Two => div_bounds(bounds, ln2_bounds(working), working),
+                    Base::Ten => div_bounds(bounds, ln10_bounds(working), working),
+                })
+            });
+            *x = result;
+            return ord
+        }
+    }
+    Ordering::Equal
+}
+
+// compute `ln(1 + x)` rounded to `prec` bits, storing the result in
+// `x` and returning how it compares to the exact value.
+fn ln_1p_in_place(x: &mut Float, prec: u32, mode: RoundingMode) -> Ordering {
+    x.debug_assert_valid();
+
+    match x.style {
+        Style::NaN => *x = Float::nan(prec),
+        Style::Infinity => {
+            *x = match x.sign {
+                Sign::Pos => Float::inf(prec, Sign::Pos),
+                Sign::Neg => Float::invalid(prec),
+            }
+        }
+        Style::Zero => *x = Float::zero_(prec, x.sign),
+        Style::Normal => {
+            if *x <= -1 {
+                *x = if *x == -1 {
+                    env::raise(Exception::DivideByZero);
+                    Float::inf(prec, Sign::Neg)
+                } else {
+                    Float::invalid(prec)
+                };
+                return Ordering::Equal
+            }
+            let bits = cmp::max(x.prec, prec);
+            let (result, ord) = if x.exp < -(bits as i64) - 2 {
+                // ln(1 + x) is between x - x**2 and x, and the
+                // difference is less than one unit in the last place
+                // of `x` with one more bit than `bits`, so nothing
+                // rounds differently between them. As for `expm1`,
+                // this is scaled to avoid the bottom of the exponent
+                // range.
+                let mut y = x.clone().abs().with_precision(bits + 1);
+                y.exp = 0;
+                round_bounds(x.sign, x.exp, prec, mode, |_| {
+                    match x.sign {
+                        Sign::Pos => (y.clone().next_below(), y.clone()),
+                        Sign::Neg => (y.clone(), y.clone().next_above()),
+                    }
+                })
+            } else if x.exp < -1 || (x.exp < 0 && x.sign == Sign::Pos) {
+                // the series is used directly, to avoid cancellation
+                round_bounds(x.sign, 0, prec, mode, |working| {
+                    magnitudes(x.sign, ln1p_bounds(x, working))
+                })
+            } else {
+                // 1 + x is either at least 2, or at most 1/2 (and
+                // then exact), so rounding it loses nothing relative
+                // to the logarithm.
+                let one = Float::from(1);
+                round_bounds(x.sign, 0, prec, mode, |working| {
+                    let lo = ln_bounds(&Float::add_to(&one, x, working, DOWN), working).0;
+                    let hi = ln_bounds(&Float::add_to(&one, x, working, UP), working).1;
+                    magnitudes(x.sign, (lo, hi))
+                })
+            };
+            *x = result;
+            return ord
+        }
+    }
+    Ordering::Equal
+}
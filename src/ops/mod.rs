@@ -1,5 +1,9 @@
 mod addsub;
 mod cmp;
 mod conv;
+mod exp;
+mod fma;
+mod log;
 mod muldiv;
+mod pow;
 mod sqrt;
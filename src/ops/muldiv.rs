@@ -1,63 +1,91 @@
-use {Style, Sign, Float, add_overflow, sub_overflow};
-use std::i64;
+use {Style, Float, RoundingMode, Exception, exp_sum};
+use env;
+use std::cmp;
+use std::cmp::Ordering;
 use std::ops::{Mul, MulAssign, Div, DivAssign};
 
 use ramp::ll::limb::Limb;
 
+// As for addition, the result of a binary operation has the larger
+// of the two precisions.
+impl Float {
+    /// Compute `self * other`, rounding according to `mode`.
+    pub fn mul_round(self, other: &Float, mode: RoundingMode) -> Float {
+        self.mul_ternary(other, mode).0
+    }
+    /// Compute `self / other`, rounding according to `mode`.
+    pub fn div_round(self, other: &Float, mode: RoundingMode) -> Float {
+        self.div_ternary(other, mode).0
+    }
+    /// Compute `self * other`, rounding according to `mode`, and
+    /// returning how the result compares to the exact product.
+    pub fn mul_ternary(mut self, other: &Float, mode: RoundingMode) -> (Float, Ordering) {
+        let prec = cmp::max(self.prec, other.prec);
+        let ord = mul_in_place(&mut self, other, prec, mode);
+        (self, ord)
+    }
+    /// Compute `self / other`, rounding according to `mode`, and
+    /// returning how the result compares to the exact quotient.
+    pub fn div_ternary(mut self, other: &Float, mode: RoundingMode) -> (Float, Ordering) {
+        let prec = cmp::max(self.prec, other.prec);
+        let ord = div_in_place(&mut self, other, prec, mode);
+        (self, ord)
+    }
+    /// Compute `x * y` rounded directly to `prec` bits according to
+    /// `mode`, independent of the precisions of `x` and `y`.
+    pub fn mul_to(x: &Float, y: &Float, prec: u32, mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut x = x.clone();
+        mul_in_place(&mut x, y, prec, mode);
+        x
+    }
+    /// Compute `x / y` rounded directly to `prec` bits according to
+    /// `mode`, independent of the precisions of `x` and `y`.
+    pub fn div_to(x: &Float, y: &Float, prec: u32, mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut x = x.clone();
+        div_in_place(&mut x, y, prec, mode);
+        x
+    }
+}
+
+// compute `x * y` rounded to `prec` bits, storing the result in `x`
+// and returning how it compares to the exact value.
+fn mul_in_place(x: &mut Float, y: &Float, prec: u32, mode: RoundingMode) -> Ordering {
+    x.debug_assert_valid();
+    y.debug_assert_valid();
+
+    match (x.style, y.style) {
+        (Style::NaN, _) | (_, Style::NaN) => *x = Float::nan(prec),
+        // 0.0 * inf, inf * 0.0 are NaN
+        (Style::Infinity, Style::Zero) | (Style::Zero, Style::Infinity) => {
+            *x = Float::invalid(prec)
+        }
+        (Style::Infinity, _) | (_, Style::Infinity) => {
+            *x = Float::inf(prec, x.sign ^ y.sign)
+        }
+        (Style::Zero, _) | (_, Style::Zero) => {
+            *x = Float::zero_(prec, x.sign ^ y.sign)
+        }
+        (Style::Normal, Style::Normal) => {
+            x.signif *= &y.signif;
+            x.sign = x.sign ^ y.sign;
+
+            // the product of a p bit and a q bit significand has
+            // either p + q - 1 or p + q bits.
+            let bits = x.signif.bit_length() as i64;
+            let exp = exp_sum(x.exp, y.exp, bits + 1 - x.prec as i64 - y.prec as i64);
+            x.prec = prec;
+            return x.normalise_at(exp, false, mode)
+        }
+    }
+    Ordering::Equal
+}
+
 impl<'a> MulAssign<&'a Float> for Float {
     fn mul_assign(&mut self, other: &'a Float) {
-        self.debug_assert_valid();
-        other.debug_assert_valid();
-        assert_eq!(self.prec, other.prec);
-        let prec = self.prec;
-
-        match (self.style, other.style) {
-            (Style::NaN, _) | (_, Style::NaN) => *self = Float::nan(prec),
-            // 0.0 * inf, inf * 0.0 are NaN
-            (Style::Infinity, Style::Zero) | (Style::Zero, Style::Infinity) => {
-                *self = Float::nan(prec)
-            }
-            (Style::Infinity, _) | (_, Style::Infinity) => {
-                *self = Float::inf(prec, self.sign ^ other.sign)
-            }
-            (Style::Zero, _) | (_, Style::Zero) => {
-                // FIXME (#3): need to get the right sign
-                *self = Float::zero_(prec, Sign::Pos)
-            }
-            (Style::Normal, Style::Normal) => {
-                self.signif *= &other.signif;
-                self.sign = self.sign ^ other.sign;
-
-                let bits = self.signif.bit_length();
-                let shift = bits - prec;
-
-                let (raw_mult_exp, o1) = add_overflow(self.exp, other.exp);
-                let (adjusted_exp, o2) = add_overflow(raw_mult_exp,
-                                                      shift as i64 - (prec - 1) as i64);
-                self.exp = adjusted_exp;
-                if o1 ^ o2 {
-                    // if we only overflowed once, then there's a
-                    // problem. A double overflow means we went over
-                    // the limit and then back, but a single means we
-                    // never returned.
-                    let overflowed = if o1 { raw_mult_exp } else { adjusted_exp };
-                    self.exp = if overflowed < 0 { i64::MAX } else { i64::MIN };
-                    self.normalise(false);
-                } else {
-                    let ulp_bit = self.signif.bit(shift);
-                    let half_ulp_bit = self.signif.bit(shift - 1);
-                    let has_trailing_ones = self.signif.trailing_zeros() < shift - 1;
-
-                    self.signif >>= shift as usize;
-
-                    let round = half_ulp_bit && (ulp_bit || has_trailing_ones);
-                    if round {
-                        self.signif += 1;
-                    }
-                    self.normalise(true);
-                }
-            }
-        }
+        let prec = cmp::max(self.prec, other.prec);
+        mul_in_place(self, other, prec, RoundingMode::NearestEven);
     }
 }
 
@@ -107,80 +135,68 @@ impl<'a> Mul<&'a Float> for &'a Float {
     }
 }
 
-impl<'a> DivAssign<&'a Float> for Float {
-    fn div_assign(&mut self, other: &'a Float) {
-        self.debug_assert_valid();
-        other.debug_assert_valid();
-        assert_eq!(self.prec, other.prec);
-        let prec = self.prec;
-
-        match (self.style, other.style) {
-            (Style::NaN, _) | (_, Style::NaN) => *self = Float::nan(prec),
-            // 0.0 / 0.0 is NaN
-            (Style::Zero, Style::Zero) => *self = Float::nan(prec),
-            // 0.0 / x == 0.0
-            (Style::Zero, _) => *self = Float::zero_(prec, self.sign),
-            // x / 0.0 == inf
-            (_, Style::Zero) => *self = Float::inf(prec, other.sign),
-            (Style::Infinity, Style::Infinity) => {
-                *self = Float::nan(prec)
-            }
-            // x / inf == 0.0
-            (_, Style::Infinity) => *self = Float::zero_(prec, other.sign),
-            // inf / x == inf (x != 0)
-            (Style::Infinity, _) => {
-                self.sign = self.sign ^ other.sign;
-            }
-            (Style::Normal, Style::Normal) => {
-                let (raw_mult_exp, o1) = sub_overflow(self.exp, other.exp);
-                let (adjusted_exp, o2) = sub_overflow(raw_mult_exp,
-                                                      (self.signif < other.signif) as i64);
-                self.sign = self.sign ^ other.sign;
-                self.exp = adjusted_exp;
-
-                if o1 ^ o2 {
-                    // if we only overflowed once, then there's a
-                    // problem. A double overflow means we went over
-                    // the limit and then back, but a single means we
-                    // never returned.
-                    let overflowed = if o1 { raw_mult_exp } else { adjusted_exp };
-                    self.exp = if overflowed < 0 { i64::MAX } else { i64::MIN };
-                    self.normalise(false);
-                } else {
-                    // we compute (m1 * 2**x) / m2 for some x >= p + 1, to
-                    // ensure we get the full significand, and the
-                    // rounding bit, and can use the remainder to check
-                    // for sticky bits.
-
-                    // round-up so that we're shifting by whole limbs,
-                    // ensuring there's no need for sub-limb shifts.
-                    let shift = (prec as usize  + 1 + Limb::BITS - 1) / Limb::BITS * Limb::BITS;
-
-                    self.signif <<= shift;
-
-                    let (q, r) = self.signif.divmod(&other.signif);
-                    self.signif.clone_from(&q);
-
-                    let bits = self.signif.bit_length();
-                    assert!(bits >= prec + 1);
-                    let unshift = bits - prec;
-
-                    let ulp_bit = self.signif.bit(unshift);
-                    let half_ulp_bit = self.signif.bit(unshift - 1);
-                    let has_trailing_ones = r != 0 || self.signif.trailing_zeros() < unshift - 1;
-
-                    self.signif >>= unshift as usize;
-
-                    if half_ulp_bit && (ulp_bit || has_trailing_ones) {
-                        self.signif += 1;
-                    }
-                    self.normalise(true);
-                }
-            }
+// compute `x / y` rounded to `prec` bits, storing the result in `x`
+// and returning how it compares to the exact value.
+fn div_in_place(x: &mut Float, y: &Float, prec: u32, mode: RoundingMode) -> Ordering {
+    x.debug_assert_valid();
+    y.debug_assert_valid();
+
+    match (x.style, y.style) {
+        (Style::NaN, _) | (_, Style::NaN) => *x = Float::nan(prec),
+        // 0.0 / 0.0 is NaN
+        (Style::Zero, Style::Zero) => *x = Float::invalid(prec),
+        // 0.0 / x == 0.0
+        (Style::Zero, _) => *x = Float::zero_(prec, x.sign ^ y.sign),
+        // inf / 0.0 == inf
+        (Style::Infinity, Style::Zero) => *x = Float::inf(prec, x.sign ^ y.sign),
+        // x / 0.0 == inf
+        (_, Style::Zero) => {
+            env::raise(Exception::DivideByZero);
+            *x = Float::inf(prec, x.sign ^ y.sign)
+        }
+        (Style::Infinity, Style::Infinity) => {
+            *x = Float::invalid(prec)
+        }
+        // x / inf == 0.0
+        (_, Style::Infinity) => *x = Float::zero_(prec, x.sign ^ y.sign),
+        // inf / x == inf (x != 0)
+        (Style::Infinity, _) => {
+            *x = Float::inf(prec, x.sign ^ y.sign)
+        }
+        (Style::Normal, Style::Normal) => {
+            // we compute (m1 * 2**s) / m2 for some s large enough
+            // that the quotient has at least prec + 1 bits, to ensure
+            // we get the full significand, and the rounding bit, and
+            // can use the remainder to check for sticky bits.
+
+            // round-up so that we're shifting by whole limbs,
+            // ensuring there's no need for sub-limb shifts.
+            let needed = cmp::max(prec as i64 + 1 + y.prec as i64 - x.prec as i64, 0) as usize;
+            let shift = (needed + Limb::BITS - 1) / Limb::BITS * Limb::BITS;
+
+            x.signif <<= shift;
+            let (q, r) = x.signif.divmod(&y.signif);
+            x.signif = q;
+            x.sign = x.sign ^ y.sign;
+
+            // x / y == q * 2**(x.exp - y.exp - x.prec + y.prec - s) + ...
+            let bits = x.signif.bit_length() as i64;
+            debug_assert!(bits > prec as i64);
+            let exp = exp_sum(x.exp, -y.exp,
+                              bits - 1 - shift as i64 - x.prec as i64 + y.prec as i64);
+            x.prec = prec;
+            return x.normalise_at(exp, r != 0, mode)
         }
     }
+    Ordering::Equal
 }
 
+impl<'a> DivAssign<&'a Float> for Float {
+    fn div_assign(&mut self, other: &'a Float) {
+        let prec = cmp::max(self.prec, other.prec);
+        div_in_place(self, other, prec, RoundingMode::NearestEven);
+    }
+}
 
 impl DivAssign<Float> for Float {
     fn div_assign(&mut self, other: Float) {
@@ -0,0 +1,232 @@
+use {Style, Sign, Float, RoundingMode, Exception};
+use env;
+use super::addsub::Exact;
+use super::exp::{round_bounds, div_ln2, sub_ln2_bounds, exp_bounds, near_one_bounds,
+                 out_of_range, lowest_bit};
+use super::log::ln_bounds;
+use ramp::Int;
+
+use std::cmp;
+use std::cmp::Ordering;
+
+// As for the binary operations, the result has the larger of the two
+// precisions.
+impl Float {
+    /// Compute `self**y`, rounding to nearest.
+    ///
+    /// The special cases are those of IEEE 754's `pow`: `x**0 == 1`
+    /// and `1**y == 1` for any `x` and `y`, even NaN; a negative
+    /// `self` only has a (real) power when `y` is an integer, and the
+    /// result is negative if `y` is odd; `0**y` is infinite, with a
+    /// `DivideByZero` exception, for `y < 0`. Results that are exactly
+    /// representable, such as `9**1.5 == 27`, are computed exactly.
+    pub fn pow(self, y: &Float) -> Float {
+        self.pow_round(y, RoundingMode::NearestEven)
+    }
+    /// Compute `self**y`, rounding according to `mode`.
+    pub fn pow_round(self, y: &Float, mode: RoundingMode) -> Float {
+        self.pow_ternary(y, mode).0
+    }
+    /// Compute `self**y`, rounding according to `mode`, and returning
+    /// how the result compares to the exact value.
+    pub fn pow_ternary(mut self, y: &Float, mode: RoundingMode) -> (Float, Ordering) {
+        let prec = cmp::max(self.prec, y.prec);
+        let ord = pow_in_place(&mut self, y, prec, mode);
+        (self, ord)
+    }
+    /// Compute `x**y` rounded directly to `prec` bits according to
+    /// `mode`, independent of the precisions of `x` and `y`.
+    pub fn pow_to(x: &Float, y: &Float, prec: u32, mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut x = x.clone();
+        pow_in_place(&mut x, y, prec, mode);
+        x
+    }
+}
+
+const DOWN: RoundingMode = RoundingMode::TowardNegative;
+const UP: RoundingMode = RoundingMode::TowardPositive;
+
+// `a**y` with sign `sign` rounded to `prec` bits according to `mode`,
+// for normal `a > 0` and `y` with `a != 1`, if it is rational (and so
+// computed exactly before rounding), or if it is so large or small
+// that it is easy to round. Otherwise, it is neither representable
+// nor halfway between two representable values.
+fn exact_pow(a: &Float, y: &Float, sign: Sign, prec: u32,
+             mode: RoundingMode) -> Option<(Float, Ordering)> {
+    // a == m * 2**e and |y| == n * 2**t, for odd `m` and `n`.
+    let mut m = &a.signif >> a.signif.trailing_zeros() as usize;
+    let mut e = lowest_bit(a);
+    let t = lowest_bit(y);
+    let n = &y.signif >> y.signif.trailing_zeros() as usize;
+
+    let k = if t < 0 {
+        // a**(n / 2**d) is irrational unless `a` is a perfect
+        // 2**d-th power, which requires both `e` to be a multiple of
+        // 2**d and `m` to be a perfect square `d` times (which
+        // quickly runs out of bits, unless `m` is 1).
+        let d = t.wrapping_neg() as u64;
+        if e != 0 && (e.trailing_zeros() as u64) < d {
+            return None
+        }
+        let mut i = 0;
+        while i < d && m != 1 {
+            let (root, rem) = m.sqrt_rem().unwrap();
+            if rem != 0 {
+                return None
+            }
+            m = root;
+            i += 1;
+        }
+        if e != 0 {
+            e >>= d as u32;
+        }
+        n
+    } else if y.exp < 64 {
+        n << t as usize
+    } else {
+        // |y| >= 2**64, so an exact power of two is far outside the
+        // range, and anything else has far too many bits.
+        return if m == 1 {
+            Some(out_of_range(prec, sign, (e > 0) == (y.sign == Sign::Pos), mode))
+        } else {
+            None
+        }
+    };
+
+    // a**y == m**(+/-k) * 2**(+/-e k), where `k` is an integer.
+    let mut low = Int::from(e) * &k;
+    if y.sign == Sign::Neg {
+        low.negate();
+    }
+    let signif = if m == 1 {
+        Int::from(1)
+    } else if y.sign == Sign::Neg {
+        // 1 / m**k is never a binary fraction.
+        return None
+    } else if Int::from(m.bit_length() - 1) * &k > Int::from(prec + 1) {
+        // m**k is odd with more than `prec + 1` bits.
+        return None
+    } else {
+        m.pow(i64::from(&k) as usize)
+    };
+    Some(Exact { sign: sign, signif: signif, low: low }.round(prec, mode))
+}
+
+// compute `x**y` rounded to `prec` bits, storing the result in `x`
+// and returning how it compares to the exact value.
+fn pow_in_place(x: &mut Float, y: &Float, prec: u32, mode: RoundingMode) -> Ordering {
+    x.debug_assert_valid();
+    y.debug_assert_valid();
+
+    // x**0 == 1**y == 1, even for NaN, and (-1)**inf == 1 too.
+    let one = match (x.style, y.style) {
+        (_, Style::Zero) => true,
+        (Style::Normal, Style::Infinity) => x.clone().abs() == 1,
+        (Style::Normal, _) => *x == 1,
+        _ => false,
+    };
+    // the power of a negative value is negative if `y` is an odd
+    // integer, and otherwise is only real if `y` is an integer.
+    let odd = match y.style {
+        Style::Normal => lowest_bit(y) == 0,
+        _ => false,
+    };
+    let sign = if odd { x.sign } else { Sign::Pos };
+
+    match (x.style, y.style) {
+        _ if one => {
+            let (one, ord) = Float::from(1).with_precision_ternary(prec, mode);
+            *x = one;
+            return ord
+        }
+        (Style::NaN, _) | (_, Style::NaN) => *x = Float::nan(prec),
+        (_, Style::Infinity) => {
+            *x = if (x.clone().abs() < 1) == (y.sign == Sign::Pos) {
+                Float::zero_(prec, Sign::Pos)
+            } else {
+                Float::inf(prec, Sign::Pos)
+            }
+        }
+        // 0**y for y < 0 is infinite, like division by zero
+        (Style::Zero, _) => {
+            *x = match y.sign {
+                Sign::Pos => Float::zero_(prec, sign),
+                Sign::Neg => {
+                    env::raise(Exception::DivideByZero);
+                    Float::inf(prec, sign)
+                }
+            }
+        }
+        (Style::Infinity, _) => {
+            *x = match y.sign {
+                Sign::Pos => Float::inf(prec, sign),
+                Sign::Neg => Float::zero_(prec, sign),
+            }
+        }
+        // `y` is normal here, as the other cases are above
+        (Style::Normal, _) => {
+            if x.sign == Sign::Neg && lowest_bit(y) < 0 {
+                *x = Float::invalid(prec);
+                return Ordering::Equal
+            }
+            let a = x.clone().abs();
+            if a == 1 {
+                // (-1)**y for an integer `y`, which may be too large
+                // for `exact_pow`.
+                let one = Exact { sign: sign, signif: Int::from(1), low: Int::zero() };
+                let (result, ord) = one.round(prec, mode);
+                *x = result;
+                return ord
+            }
+            if let Some((result, ord)) = exact_pow(&a, y, sign, prec, mode) {
+                *x = result;
+                return ord
+            }
+
+            // a**y == e**z for z == y ln(a), and e**z == e**r * 2**k
+            // where `r` is small.
+            let z = env::quietly(|| Float::mul_to(y, &ln_bounds(&a, 64).0, 64,
+                                                  RoundingMode::NearestEven));
+            // a**y > 1 exactly when `y` and `ln(a)` have the same sign
+            let z_sign = if (y.sign == Sign::Pos) == (a > 1) { Sign::Pos } else { Sign::Neg };
+            let k = match z.style {
+                Style::Normal if z.exp < 63 => div_ln2(&z),
+                _ => None,
+            };
+            let tiny = match z.style {
+                Style::Zero => true,
+                Style::Normal => z.exp < -(prec as i64) - 3,
+                _ => false,
+            };
+            let (result, ord) = match k {
+                // as for `e**x`, a**y is very close to 1.
+                _ if tiny => {
+                    round_bounds(sign, 0, prec, mode, |_| near_one_bounds(z_sign, prec))
+                }
+                Some(k) => {
+                    round_bounds(sign, k, prec, mode, |working| {
+                        // z may be large, and needs to be accurate in
+                        // absolute terms.
+                        let work = working + cmp::max(z.exp, 0) as u32 + 8;
+                        let (ln_lo, ln_hi) = ln_bounds(&a, work);
+                        let (z_lo, z_hi) = match y.sign {
+                            Sign::Pos => (Float::mul_to(y, &ln_lo, work, DOWN),
+                                          Float::mul_to(y, &ln_hi, work, UP)),
+                            Sign::Neg => (Float::mul_to(y, &ln_hi, work, DOWN),
+                                          Float::mul_to(y, &ln_lo, work, UP)),
+                        };
+                        (exp_bounds(&sub_ln2_bounds(&z_lo, k, working).0, working).0,
+                         exp_bounds(&sub_ln2_bounds(&z_hi, k, working).1, working).1)
+                    })
+                }
+                // |z / ln(2)| >= 2**63, so a**y is far outside the
+                // range.
+                None => out_of_range(prec, sign, z_sign == Sign::Pos, mode),
+            };
+            *x = result;
+            return ord
+        }
+    }
+    Ordering::Equal
+}
@@ -1,61 +1,84 @@
-use {Style, Sign, Float};
+use {Style, Sign, Float, RoundingMode};
+use ramp::Int;
+
+use std::{cmp, mem};
+use std::cmp::Ordering;
 
 impl Float {
-    pub fn sqrt(mut self) -> Float {
-        self.debug_assert_valid();
+    pub fn sqrt(self) -> Float {
+        self.sqrt_round(RoundingMode::NearestEven)
+    }
+    /// Compute the square root of `self`, rounding according to
+    /// `mode`.
+    pub fn sqrt_round(self, mode: RoundingMode) -> Float {
+        self.sqrt_ternary(mode).0
+    }
+    /// Compute the square root of `self`, rounding according to
+    /// `mode`, and returning how the result compares to the exact
+    /// square root.
+    pub fn sqrt_ternary(mut self, mode: RoundingMode) -> (Float, Ordering) {
         let prec = self.prec;
+        let ord = sqrt_in_place(&mut self, prec, mode);
+        (self, ord)
+    }
+    /// Compute the square root of `x` rounded directly to `prec`
+    /// bits according to `mode`, independent of the precision of
+    /// `x`.
+    pub fn sqrt_to(x: &Float, prec: u32, mode: RoundingMode) -> Float {
+        assert!(prec > 0);
+        let mut x = x.clone();
+        sqrt_in_place(&mut x, prec, mode);
+        x
+    }
+}
+
+// compute the square root of `x` rounded to `prec` bits, storing the
+// result in `x` and returning how it compares to the exact value.
+fn sqrt_in_place(x: &mut Float, prec: u32, mode: RoundingMode) -> Ordering {
+    x.debug_assert_valid();
 
-        match self.style {
-            Style::NaN => Float::nan(prec),
-            Style::Infinity => {
-                match self.sign {
-                    Sign::Pos => Float::inf(prec, Sign::Pos),
-                    Sign::Neg => Float::nan(prec),
-                }
+    match x.style {
+        Style::NaN => *x = Float::nan(prec),
+        Style::Infinity => {
+            *x = match x.sign {
+                Sign::Pos => Float::inf(prec, Sign::Pos),
+                Sign::Neg => Float::invalid(prec),
             }
-            Style::Zero => Float::zero_(prec, self.sign),
-            Style::Normal => {
-                if self.sign == Sign::Neg {
-                    return Float::nan(prec);
-                }
-
-                // use this instead of % 2 to get the right sign
-                // (should be 0 or 1, even if exp is negative)
-                let c = self.exp & 1;
-                let exp = (self.exp - c) / 2;
-
-                // we compute sqrt(m1 * 2**c * 2**(p + 1)) to ensure
-                // we get the full significand, and the rounding bit,
-                // and can use the remainder to check for sticky bits.
-                let shift = prec as usize + 1 + c as usize;
-                self.signif <<= shift;
-
-                let (mut sqrt, rem) = self.signif.sqrt_rem().unwrap();
-
-                let bits = sqrt.bit_length();
-                assert!(bits >= prec + 1);
-                let unshift = bits - prec;
-
-                let ulp_bit = sqrt.bit(unshift);
-                let half_ulp_bit = sqrt.bit(unshift - 1);
-                let has_trailing_ones = rem != 0 || sqrt.trailing_zeros() < unshift - 1;
-
-                sqrt >>= unshift as usize;
-
-                let mut ret = Float {
-                    prec: prec,
-                    sign: Sign::Pos,
-                    exp: exp,
-                    signif: sqrt,
-                    style: Style::Normal,
-                };
-
-                if half_ulp_bit && (ulp_bit || has_trailing_ones) {
-                    ret.add_ulp();
-                }
-
-                ret
+        }
+        Style::Zero => *x = Float::zero_(prec, x.sign),
+        Style::Normal => {
+            if x.sign == Sign::Neg {
+                *x = Float::invalid(prec);
+                return Ordering::Equal
+            }
+
+            // use this instead of % 2 to get the right sign
+            // (should be 0 or 1, even if exp is negative)
+            let c = x.exp & 1;
+            let half_exp = (x.exp - c) / 2;
+
+            // x == m * 2**(2 * half_exp + c - p + 1), and we compute
+            // sqrt(m * 2**s) for some s that makes the remaining
+            // power of two even, and is large enough to ensure we get
+            // the full significand and the rounding bit, and can use
+            // the remainder to check for sticky bits.
+            let p = x.prec as i64;
+            let mut shift = cmp::max(2 * prec as i64 + 2 - p, 0);
+            if (c - p + 1 - shift) & 1 != 0 {
+                shift += 1;
             }
+            let mut signif = mem::replace(&mut x.signif, Int::zero());
+            signif <<= shift as usize;
+
+            let (sqrt, rem) = signif.sqrt_rem().unwrap();
+
+            let bits = sqrt.bit_length() as i64;
+            debug_assert!(bits > prec as i64);
+            x.signif = sqrt;
+            x.exp = half_exp + (c - p + 1 - shift) / 2 + bits - 1;
+            x.prec = prec;
+            return x.normalise(rem != 0, mode)
         }
     }
+    Ordering::Equal
 }
@@ -0,0 +1,550 @@
+use {Style, Sign, Float, RoundingMode, exp_sum};
+use env;
+use format::{scale_bounds, EXACT_SLACK};
+use ramp::Int;
+
+use std::{cmp, error, fmt, i64};
+use std::str::FromStr;
+
+/// An error from parsing a `Float` from a string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseFloatError {
+    kind: ErrorKind,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum ErrorKind {
+    Empty,
+    Invalid,
+}
+
+impl ParseFloatError {
+    fn new(kind: ErrorKind) -> ParseFloatError {
+        ParseFloatError { kind: kind }
+    }
+}
+
+impl fmt::Display for ParseFloatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        error::Error::description(self).fmt(f)
+    }
+}
+
+impl error::Error for ParseFloatError {
+    fn description(&self) -> &str {
+        match self.kind {
+            ErrorKind::Empty => "cannot parse float from empty string",
+            ErrorKind::Invalid => "invalid float literal",
+        }
+    }
+}
+
+// The value of a successfully parsed string, other than the sign.
+enum Parsed {
+    NaN,
+    Infinity,
+    // the digits, as an integer, scaled by `radix**exp`
+    Finite(Int, u32, i64),
+}
+
+// Split the sign off the front of `s`.
+fn parse_sign(s: &str) -> (Sign, &str) {
+    if s.starts_with('-') {
+        (Sign::Neg, &s[1..])
+    } else if s.starts_with('+') {
+        (Sign::Pos, &s[1..])
+    } else {
+        (Sign::Pos, s)
+    }
+}
+
+// Parse infinity or NaN. In bases above 16, where the plain names
+// could be digits, they have to be written as `@inf@` or `@nan@`.
+fn parse_special(s: &str, radix: u32) -> Option<Parsed> {
+    let lower = s.to_lowercase();
+    match &*lower {
+        "@inf@" => Some(Parsed::Infinity),
+        "@nan@" => Some(Parsed::NaN),
+        "inf" | "infinity" if radix <= 16 => Some(Parsed::Infinity),
+        "nan" if radix <= 16 => Some(Parsed::NaN),
+        _ => None,
+    }
+}
+
+// Parse an exponent: an optional sign followed by digits in base
+// `radix`, saturating if it is too large to fit in an `i64`.
+fn parse_exp(s: &str, radix: u32) -> Result<i64, ParseFloatError> {
+    let (sign, digits) = parse_sign(s);
+    if digits.is_empty() {
+        return Err(ParseFloatError::new(ErrorKind::Invalid))
+    }
+    let mut exp = 0i64;
+    for c in digits.chars() {
+        let d = try!(c.to_digit(radix).ok_or(ParseFloatError::new(ErrorKind::Invalid)));
+        exp = exp.saturating_mul(radix as i64).saturating_add(d as i64);
+    }
+    Ok(match sign {
+        Sign::Pos => exp,
+        Sign::Neg => -exp,
+    })
+}
+
+// Parse the digits of a number in base `radix`, with an optional
+// point, returning the digits as an integer and the (negated) number
+// of them after the point.
+fn parse_digits(s: &str, radix: u32) -> Result<(Int, i64), ParseFloatError> {
+    let mut digits = String::with_capacity(s.len());
+    let mut frac = None;
+    for (i, c) in s.char_indices() {
+        if c == '.' && frac.is_none() {
+            frac = Some(s.len() - i - 1);
+        } else if c.is_digit(radix) {
+            digits.push(c);
+        } else {
+            return Err(ParseFloatError::new(ErrorKind::Invalid))
+        }
+    }
+    if digits.is_empty() {
+        return Err(ParseFloatError::new(ErrorKind::Invalid))
+    }
+    // trailing zeros after the point don't change the value, and
+    // dropping them keeps the scale small.
+    if let Some(ref mut frac) = frac {
+        let len = cmp::max(digits.trim_right_matches('0').len(), digits.len() - *frac);
+        *frac -= digits.len() - len;
+        digits.truncate(cmp::max(len, 1));
+    }
+    let n = try!(Int::from_str_radix(&digits, radix as u8)
+                     .map_err(|_| ParseFloatError::new(ErrorKind::Invalid)));
+    Ok((n, -(frac.unwrap_or(0) as i64)))
+}
+
+// Parse a number, which is a decimal one, like `-1.25e3` or `.5`, a
+// hexadecimal one, like `0x1.8p-3`, or `inf` or `NaN`.
+fn parse_number(s: &str) -> Result<(Sign, Parsed), ParseFloatError> {
+    if s.is_empty() {
+        return Err(ParseFloatError::new(ErrorKind::Empty))
+    }
+    let (sign, s) = parse_sign(s);
+    if let Some(special) = parse_special(s, 10) {
+        return Ok((sign, special))
+    }
+
+    let parsed = if s.starts_with("0x") || s.starts_with("0X") {
+        try!(parse_hex(&s[2..]))
+    } else {
+        try!(parse_positional(s, 10))
+    };
+    Ok((sign, parsed))
+}
+
+// Parse a number in base `radix`, like `-1.2@3` (meaning `-1.2 *
+// radix**3`), or infinity or NaN.
+fn parse_radix(s: &str, radix: u32) -> Result<(Sign, Parsed), ParseFloatError> {
+    if s.is_empty() {
+        return Err(ParseFloatError::new(ErrorKind::Empty))
+    }
+    let (sign, s) = parse_sign(s);
+    if let Some(special) = parse_special(s, radix) {
+        return Ok((sign, special))
+    }
+    Ok((sign, try!(parse_positional(s, radix))))
+}
+
+// Parse the digits and exponent of a number in base `radix`, like
+// `1.25e3`. The exponent is in decimal, and is marked by `@`, or
+// also `e` or `E` in bases up to 10 (where they aren't digits).
+fn parse_positional(s: &str, radix: u32) -> Result<Parsed, ParseFloatError> {
+    let marker = s.find(|c| c == '@' || (radix <= 10 && (c == 'e' || c == 'E')));
+    let (mantissa, exp) = match marker {
+        Some(i) => (&s[..i], try!(parse_exp(&s[i + 1..], 10))),
+        None => (s, 0),
+    };
+    let (n, scale) = try!(parse_digits(mantissa, radix));
+    Ok(Parsed::Finite(n, radix, exp.saturating_add(scale)))
+}
+
+// Parse the digits and binary exponent of a hexadecimal number after
+// the `0x`, like `1.8p-3`.
+fn parse_hex(s: &str) -> Result<Parsed, ParseFloatError> {
+    let (mantissa, exp) = match s.find(|c| c == 'p' || c == 'P') {
+        Some(i) => (&s[..i], try!(parse_exp(&s[i + 1..], 10))),
+        None => (s, 0),
+    };
+    let (n, scale) = try!(parse_digits(mantissa, 16));
+    Ok(Parsed::Finite(n, 2, exp.saturating_add(scale.saturating_mul(4))))
+}
+
+// Parse a number in base `radix` in the format read by MPFR's
+// `mpfr_inp_str`, which is that of `parse_radix` with an optional
+// `0b` or `0x` prefix in base 2 or 16 respectively, where the
+// exponent can also be a (decimal) power of two after a `p` or `P`.
+fn parse_mpfr(s: &str, radix: u32) -> Result<(Sign, Parsed), ParseFloatError> {
+    if s.is_empty() {
+        return Err(ParseFloatError::new(ErrorKind::Empty))
+    }
+    let (sign, mut s) = parse_sign(s);
+    if let Some(special) = parse_special(s, radix) {
+        return Ok((sign, special))
+    }
+    let prefix = match radix {
+        2 => Some(("0b", "0B")),
+        16 => Some(("0x", "0X")),
+        _ => None,
+    };
+    if let Some((lower, upper)) = prefix {
+        if s.starts_with(lower) || s.starts_with(upper) {
+            s = &s[2..];
+        }
+    }
+
+    let binary = match prefix {
+        Some(_) => s.find(|c| c == 'p' || c == 'P'),
+        None => None,
+    };
+    match binary {
+        Some(i) => {
+            let exp = try!(parse_exp(&s[i + 1..], 10));
+            let (n, scale) = try!(parse_digits(&s[..i], radix));
+            let twos = radix.trailing_zeros() as i64;
+            Ok((sign, Parsed::Finite(n, 2, exp.saturating_add(scale.saturating_mul(twos)))))
+        }
+        None => Ok((sign, try!(parse_positional(s, radix)))),
+    }
+}
+
+// Parse a number in the format read by GMP's `mpf_set_str`, which is
+// that of `parse_radix` except that the exponent is in base `radix`
+// too, unless `radix` is negative, when it is in decimal (and the
+// digits are in base `-radix`).
+fn parse_mpf(s: &str, radix: i32) -> Result<(Sign, Parsed), ParseFloatError> {
+    if s.is_empty() {
+        return Err(ParseFloatError::new(ErrorKind::Empty))
+    }
+    let (sign, s) = parse_sign(s);
+    let digit_radix = radix.abs() as u32;
+    let exp_radix = if radix < 0 { 10 } else { digit_radix };
+    // GMP has no special values, but accept the unambiguous forms
+    // written by `to_mpf_string`.
+    if let Some(special) = parse_special(s, 36) {
+        return Ok((sign, special))
+    }
+
+    let marker = s.find(|c| c == '@' || (digit_radix <= 10 && (c == 'e' || c == 'E')));
+    let (mantissa, exp) = match marker {
+        Some(i) => (&s[..i], try!(parse_exp(&s[i + 1..], exp_radix))),
+        None => (s, 0),
+    };
+    let (n, scale) = try!(parse_digits(mantissa, digit_radix));
+    Ok((sign, Parsed::Finite(n, digit_radix, exp.saturating_add(scale))))
+}
+
+// `n * radix**exp` with sign `sign`, correctly rounded to `prec` bits
+// according to `mode`, for a non-negative `n`.
+pub fn from_scaled(sign: Sign, n: Int, radix: u32, exp: i64, prec: u32,
+                   mode: RoundingMode) -> Float {
+    assert!(prec > 0);
+    if n == 0 {
+        return Float::zero_(prec, sign)
+    }
+    let bits = n.bit_length();
+    let mut x = Float {
+        prec: prec,
+        sign: sign,
+        exp: 0,
+        signif: n,
+        style: Style::Normal,
+    };
+
+    // radix == 2**twos * odd
+    let twos = radix.trailing_zeros();
+    let odd = radix >> twos;
+
+    // values far outside the range of any exponent are handled
+    // directly, so that the exponents below can't overflow.
+    let log2 = exp as f64 * (radix as f64).log2() + bits as f64;
+    let limit = i64::MAX as f64 * 1.125;
+    if log2 > limit {
+        x.normalise_at((i64::MAX, true), false, mode);
+        return x
+    } else if log2 < -limit {
+        x.normalise_at((i64::MIN, true), false, mode);
+        return x
+    }
+
+    let cost = (exp as f64).abs() * (odd as f64).log2();
+    if cost <= 2.0 * (prec + bits) as f64 + EXACT_SLACK {
+        exact_from_scaled(&mut x, odd, twos, exp, mode);
+    } else {
+        approx_from_scaled(&mut x, odd, twos, exp, mode);
+    }
+    x
+}
+
+fn exact_from_scaled(x: &mut Float, odd: u32, twos: u32, exp: i64, mode: RoundingMode) {
+    // x * radix**exp == x.signif * odd**exp * 2**(exp * twos), where
+    // the last exponent is split in two, as it can be just outside
+    // the range of `i64` even when the result is inside it.
+    let half = twos as i64 / 2;
+    let (exp2_lo, exp2_hi) = (exp * half, exp * (twos as i64 - half));
+    let mut sticky = false;
+    let mut shift = 0;
+    if odd > 1 {
+        if exp >= 0 {
+            x.signif *= &Int::from(odd).pow(exp as usize);
+        } else {
+            // compute enough bits of the quotient to round, with the
+            // remainder giving the sticky bit.
+            let den = Int::from(odd).pow(-exp as usize);
+            shift = cmp::max(x.prec as i64 + 2 + den.bit_length() as i64
+                             - x.signif.bit_length() as i64, 0);
+            x.signif <<= shift as usize;
+            let (q, r) = x.signif.divmod(&den);
+            x.signif = q;
+            sticky = r != 0;
+        }
+    }
+    let bits = x.signif.bit_length() as i64;
+    let e = exp_sum(bits - 1 - shift, exp2_hi, exp2_lo);
+    x.normalise_at(e, sticky, mode);
+}
+
+fn approx_from_scaled(x: &mut Float, odd: u32, twos: u32, exp: i64, mode: RoundingMode) {
+    // the rounding of the bounds below is done in the caller's
+    // exponent range, not the default one used inside `quietly`.
+    let (emin, emax) = Float::exp_range();
+    let (prec, sign) = (x.prec, x.sign);
+    let beyond = if exp > 0 { (i64::MAX, true) } else { (i64::MIN, true) };
+
+    // x == n * odd**exp * 2**scale, where `n` is scaled so that the
+    // first two factors are near 1, and so stay inside the range of
+    // exponents even when the result is at the edge of it.
+    let log = (exp as f64 * (odd as f64).log2()).round();
+    if log.abs() >= i64::MAX as f64 {
+        x.normalise_at(beyond, false, mode);
+        return
+    }
+    let (scale, overflowed) = exp_sum(log as i64, exp * twos as i64,
+                                      x.signif.bit_length() as i64 - 1);
+    if overflowed {
+        x.normalise_at((scale, true), false, mode);
+        return
+    }
+    let mut n = Float::from(x.signif.clone());
+    n.exp = -(log as i64);
+    let (emin, emax) = (emin.saturating_sub(scale), emax.saturating_sub(scale));
+
+    let bound = env::quietly(|| {
+        let mut working = prec + 64;
+        loop {
+            let (mut lo, mut hi) = scale_bounds(&n, odd, exp, working);
+            lo.sign = sign;
+            hi.sign = sign;
+            if lo == hi {
+                return (lo, false)
+            }
+            // the exact value lies strictly between `lo` and `hi`,
+            // so if they round to the same value, so does it.
+            let mut rounded = lo.clone();
+            rounded.prec = prec;
+            rounded.normalise_in(true, mode, emin, emax);
+            hi.prec = prec;
+            hi.normalise_in(true, mode, emin, emax);
+            if rounded == hi {
+                return (lo, true)
+            }
+            working *= 2;
+        }
+    });
+
+    let (lo, sticky) = bound;
+    let e = exp_sum(lo.exp, scale, 0);
+    *x = lo;
+    x.prec = prec;
+    x.normalise_at(e, sticky, mode);
+}
+
+// Parse the output of `Debug`.
+fn parse_debug(s: &str) -> Result<Float, ParseFloatError> {
+    if s.is_empty() {
+        return Err(ParseFloatError::new(ErrorKind::Empty))
+    }
+    let invalid = ParseFloatError::new(ErrorKind::Invalid);
+
+    // the special values are followed by their precision.
+    if s.ends_with(')') {
+        if let Some(i) = s.rfind(" (prec ") {
+            let prec = try!(parse_prec(&s[i + 7..s.len() - 1]));
+            return match &s[..i] {
+                "NaN" => Ok(Float::nan(prec)),
+                "-NaN" => {
+                    let mut nan = Float::nan(prec);
+                    nan.negate();
+                    Ok(nan)
+                }
+                "+0.0" => Ok(Float::zero_(prec, Sign::Pos)),
+                "-0.0" => Ok(Float::zero_(prec, Sign::Neg)),
+                "+inf" => Ok(Float::inf(prec, Sign::Pos)),
+                "-inf" => Ok(Float::inf(prec, Sign::Neg)),
+                _ => Err(invalid),
+            }
+        }
+    }
+
+    // +<bits> * 2^(<exp> - <prec>)
+    let sign = if s.starts_with('+') {
+        Sign::Pos
+    } else if s.starts_with('-') {
+        Sign::Neg
+    } else {
+        return Err(invalid)
+    };
+    let s = &s[1..];
+    let (bits, rest) = match s.find(" * 2^(") {
+        Some(i) => (&s[..i], &s[i + 6..]),
+        None => return Err(invalid),
+    };
+    if !rest.ends_with(')') {
+        return Err(invalid)
+    }
+    let rest = &rest[..rest.len() - 1];
+    // the exponent can be negative, so the separator is the last one.
+    let (exp_str, prec) = match rest.rfind(" - ") {
+        Some(i) => (&rest[..i], &rest[i + 3..]),
+        None => return Err(invalid),
+    };
+    let prec = try!(parse_prec(prec));
+    let exp = match exp_str.parse::<i64>() {
+        Ok(exp) if !exp_str.starts_with('+') && exp != i64::MIN && exp != i64::MAX => exp,
+        _ => return Err(invalid),
+    };
+    if !bits.starts_with('1') || bits.len() != prec as usize ||
+        !bits.chars().all(|c| c == '0' || c == '1')
+    {
+        return Err(invalid)
+    }
+    Ok(Float {
+        prec: prec,
+        sign: sign,
+        exp: exp,
+        signif: Int::from_str_radix(bits, 2).unwrap(),
+        style: Style::Normal,
+    })
+}
+
+fn parse_prec(s: &str) -> Result<u32, ParseFloatError> {
+    match s.parse::<u32>() {
+        Ok(prec) if prec > 0 && !s.starts_with('+') => Ok(prec),
+        _ => Err(ParseFloatError::new(ErrorKind::Invalid)),
+    }
+}
+
+fn from_parsed(sign: Sign, parsed: Parsed, prec: u32, mode: RoundingMode) -> Float {
+    match parsed {
+        Parsed::NaN => Float::nan(prec),
+        Parsed::Infinity => Float::inf(prec, sign),
+        Parsed::Finite(n, radix, exp) => from_scaled(sign, n, radix, exp, prec, mode),
+    }
+}
+
+impl Float {
+    /// Parse a number from `s`, rounding it to `prec` bits according
+    /// to `mode`.
+    ///
+    /// `s` is an optional sign followed by either `inf`, `infinity`
+    /// or `nan` (in any case), or a number of decimal digits with an
+    /// optional point and an optional exponent, like `1.5e-3`, or a
+    /// hexadecimal number in the format printed by `LowerHex`, like
+    /// `0x1.8p-3` (that is, `0x1.8 * 2**-3`), where the `p` and
+    /// exponent are optional. The result is correctly rounded,
+    /// however many digits there are.
+    pub fn from_str_with_precision(s: &str, prec: u32, mode: RoundingMode)
+                                   -> Result<Float, ParseFloatError> {
+        assert!(prec > 0);
+        let (sign, parsed) = try!(parse_number(s));
+        Ok(from_parsed(sign, parsed, prec, mode))
+    }
+
+    /// Parse the output of the `Debug` impl, giving back exactly the
+    /// value that was printed, including its precision (and the
+    /// sign of a zero or NaN). See `Debug` for the format.
+    pub fn from_debug_str(s: &str) -> Result<Float, ParseFloatError> {
+        parse_debug(s)
+    }
+
+    /// Parse a number in base `radix` from `s`, rounding it to
+    /// `prec` bits according to `mode`.
+    ///
+    /// `s` is an optional sign followed by digits in base `radix`
+    /// (with letters for digits above 9, in either case), with an
+    /// optional point and an optional exponent, like `-z.z@-3`, in
+    /// the format written by `to_string_radix`. The exponent is
+    /// written in decimal after an `@` (or also `e` or `E` in bases
+    /// up to 10), and is a power of `radix`. Infinity and NaN are
+    /// `@inf@` and `@nan@`, or also `inf`, `infinity` and `nan` in
+    /// bases up to 16. The result is correctly rounded.
+    ///
+    /// This panics if `radix` is not between 2 and 36 (inclusive).
+    pub fn from_str_radix(s: &str, radix: u32, prec: u32, mode: RoundingMode)
+                          -> Result<Float, ParseFloatError> {
+        assert!(2 <= radix && radix <= 36, "invalid radix {}", radix);
+        assert!(prec > 0);
+        let (sign, parsed) = try!(parse_radix(s, radix));
+        Ok(from_parsed(sign, parsed, prec, mode))
+    }
+
+    /// Parse a number in base `radix` from `s` in the format read by
+    /// MPFR's `mpfr_inp_str` (and `mpfr_set_str`), rounding it to
+    /// `prec` bits according to `mode`.
+    ///
+    /// This is the format of `from_str_radix`, like `0.1101@5` or
+    /// `-1.5e-3`, along with MPFR's extensions for bases 2 and 16: an
+    /// optional `0b` or `0x` prefix, and a binary exponent after a `p`
+    /// or `P`, like `0x1.8p-3`. Reading the output of
+    /// `to_mpfr_string` at the precision of the value that was
+    /// written gives back exactly that value.
+    ///
+    /// This panics if `radix` is not between 2 and 36 (inclusive).
+    pub fn from_mpfr_str(s: &str, radix: u32, prec: u32, mode: RoundingMode)
+                         -> Result<Float, ParseFloatError> {
+        assert!(2 <= radix && radix <= 36, "invalid radix {}", radix);
+        assert!(prec > 0);
+        let (sign, parsed) = try!(parse_mpfr(s, radix));
+        Ok(from_parsed(sign, parsed, prec, mode))
+    }
+
+    /// Parse a number from `s` in the format read by GMP's
+    /// `mpf_set_str` (and `mpf_inp_str`), rounding it to `prec` bits
+    /// according to `mode`.
+    ///
+    /// `s` is an optional sign followed by digits in base `radix`
+    /// with an optional point, like `0.1101`, and an optional
+    /// exponent after an `@` (or also `e` or `E` in bases up to 10),
+    /// which is a power of `radix`. As in GMP, the exponent is itself
+    /// written in base `radix`, so `0.1101@101` in base 2 is `26`; if
+    /// `radix` is negative, the digits are in base `-radix` and the
+    /// exponent is in decimal, as written by `mpf_out_str` and
+    /// `to_mpf_string`. GMP has no infinities or NaN, but `@inf@` and
+    /// `@nan@` are accepted too. Reading the output of
+    /// `to_mpf_string` with the negated base at the precision of the
+    /// value that was written gives back exactly that value.
+    ///
+    /// This panics if `radix` is not between 2 and 36 (inclusive), or
+    /// -36 and -2.
+    pub fn from_mpf_str(s: &str, radix: i32, prec: u32, mode: RoundingMode)
+                        -> Result<Float, ParseFloatError> {
+        assert!(2 <= radix.abs() && radix.abs() <= 36, "invalid radix {}", radix);
+        assert!(prec > 0);
+        let (sign, parsed) = try!(parse_mpf(s, radix));
+        Ok(from_parsed(sign, parsed, prec, mode))
+    }
+}
+
+/// Parse a decimal or hexadecimal number to a precision of 53 bits
+/// (like `f64`), rounding to nearest; see `from_str_with_precision`.
+impl FromStr for Float {
+    type Err = ParseFloatError;
+    fn from_str(s: &str) -> Result<Float, ParseFloatError> {
+        Float::from_str_with_precision(s, 53, RoundingMode::NearestEven)
+    }
+}
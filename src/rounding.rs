@@ -0,0 +1,57 @@
+use sign::Sign;
+
+use std::cmp::Ordering;
+
+/// The rule used to choose a representable value when the exact
+/// result of an operation doesn't fit in the destination precision.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum RoundingMode {
+    /// Round to the nearest representable value, with ties going to
+    /// the one with an even significand. This is the IEEE 754
+    /// default, and what the operators (`+`, `*`, etc.) use.
+    NearestEven,
+    /// Round to the nearest representable value, with ties going
+    /// away from zero.
+    NearestAway,
+    /// Round toward zero, i.e. truncate.
+    TowardZero,
+    /// Round toward positive infinity.
+    TowardPositive,
+    /// Round toward negative infinity.
+    TowardNegative,
+}
+
+impl Default for RoundingMode {
+    fn default() -> RoundingMode {
+        RoundingMode::NearestEven
+    }
+}
+
+// Whether a value with the given sign, which has been truncated to a
+// significand with lowest bit `ulp_bit`, should have its magnitude
+// increased by one ulp. `half_ulp_bit` is the first bit that was
+// removed, and `sticky` is whether any of the bits below that were
+// set.
+pub fn round_away(mode: RoundingMode, sign: Sign,
+                  ulp_bit: bool, half_ulp_bit: bool, sticky: bool) -> bool {
+    match mode {
+        RoundingMode::NearestEven => half_ulp_bit && (ulp_bit || sticky),
+        RoundingMode::NearestAway => half_ulp_bit,
+        RoundingMode::TowardZero => false,
+        RoundingMode::TowardPositive => sign == Sign::Pos && (half_ulp_bit || sticky),
+        RoundingMode::TowardNegative => sign == Sign::Neg && (half_ulp_bit || sticky),
+    }
+}
+
+// How a rounded value with sign `sign` compares to the exact value,
+// given whether its magnitude was rounded up, and whether it is
+// inexact at all.
+pub fn ternary(sign: Sign, away: bool, inexact: bool) -> Ordering {
+    if !inexact {
+        Ordering::Equal
+    } else if away == (sign == Sign::Pos) {
+        Ordering::Greater
+    } else {
+        Ordering::Less
+    }
+}
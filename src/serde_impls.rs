@@ -0,0 +1,139 @@
+use {Style, Sign, Float};
+use bytes::{int_to_le_bytes, int_from_le_bytes};
+
+use serde::{Serialize, Serializer, Deserialize, Deserializer};
+use serde::de::{self, Visitor, SeqAccess};
+use serde::ser::SerializeTuple;
+
+use std::{fmt, i64};
+
+// Human-readable formats get the output of `Debug`, which is
+// lossless, and others get a tuple `(prec, negative, exp, signif)`
+// where `signif` is the little-endian bytes of the significand. As
+// in IEEE 754, an exponent of `i64::MIN` is a zero, and one of
+// `i64::MAX` is an infinity if the significand is empty, and a NaN
+// otherwise.
+
+/// Serializes as a string in the format printed by `Debug` if the
+/// format is human-readable, and otherwise as a tuple of the
+/// precision, whether the value is negative, the exponent and the
+/// bytes of the significand. Both are lossless.
+impl Serialize for Float {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.debug_assert_valid();
+        if serializer.is_human_readable() {
+            return serializer.collect_str(&format_args!("{:?}", self))
+        }
+        let signif = match self.style {
+            Style::NaN => vec![1],
+            Style::Normal => int_to_le_bytes(&self.signif),
+            Style::Zero | Style::Infinity => vec![],
+        };
+        let mut t = try!(serializer.serialize_tuple(4));
+        try!(t.serialize_element(&self.prec));
+        try!(t.serialize_element(&(self.sign == Sign::Neg)));
+        try!(t.serialize_element(&self.exp));
+        try!(t.serialize_element(&Bytes(signif)));
+        t.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Float {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Float, D::Error> {
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_str(DebugVisitor)
+        } else {
+            deserializer.deserialize_tuple(4, TupleVisitor)
+        }
+    }
+}
+
+struct DebugVisitor;
+
+impl<'de> Visitor<'de> for DebugVisitor {
+    type Value = Float;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a Float in the format printed by Debug")
+    }
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Float, E> {
+        Float::from_debug_str(s).map_err(|_| E::invalid_value(de::Unexpected::Str(s), &self))
+    }
+}
+
+struct TupleVisitor;
+
+impl<'de> Visitor<'de> for TupleVisitor {
+    type Value = Float;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a tuple of a Float's precision, sign, exponent and significand")
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Float, A::Error> {
+        let prec: u32 = try!(try!(seq.next_element()).ok_or(de::Error::invalid_length(0, &self)));
+        let neg: bool = try!(try!(seq.next_element()).ok_or(de::Error::invalid_length(1, &self)));
+        let exp: i64 = try!(try!(seq.next_element()).ok_or(de::Error::invalid_length(2, &self)));
+        let signif: Bytes = try!(try!(seq.next_element())
+                                     .ok_or(de::Error::invalid_length(3, &self)));
+        let signif = signif.0;
+
+        let sign = if neg { Sign::Neg } else { Sign::Pos };
+        let x = match exp {
+            _ if prec == 0 => None,
+            i64::MIN if signif.is_empty() => Some(Float::zero_(prec, sign)),
+            i64::MAX if signif.is_empty() => Some(Float::inf(prec, sign)),
+            i64::MAX => {
+                let mut nan = Float::nan(prec);
+                nan.sign = sign;
+                Some(nan)
+            }
+            _ => {
+                let x = Float {
+                    prec: prec,
+                    sign: sign,
+                    exp: exp,
+                    signif: int_from_le_bytes(&signif),
+                    style: Style::Normal,
+                };
+                if x.is_valid() { Some(x) } else { None }
+            }
+        };
+        x.ok_or(de::Error::invalid_value(de::Unexpected::Other("invalid Float parts"), &self))
+    }
+}
+
+// A byte string, which serializes with `serialize_bytes` rather than
+// as a sequence.
+struct Bytes(Vec<u8>);
+
+impl Serialize for Bytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bytes {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Bytes, D::Error> {
+        deserializer.deserialize_byte_buf(BytesVisitor)
+    }
+}
+
+struct BytesVisitor;
+
+impl<'de> Visitor<'de> for BytesVisitor {
+    type Value = Bytes;
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("bytes")
+    }
+    fn visit_bytes<E: de::Error>(self, b: &[u8]) -> Result<Bytes, E> {
+        Ok(Bytes(b.to_vec()))
+    }
+    fn visit_byte_buf<E: de::Error>(self, b: Vec<u8>) -> Result<Bytes, E> {
+        Ok(Bytes(b))
+    }
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Bytes, A::Error> {
+        let mut v = Vec::new();
+        while let Some(b) = try!(seq.next_element()) {
+            v.push(b);
+        }
+        Ok(Bytes(v))
+    }
+}
@@ -0,0 +1,163 @@
+extern crate float;
+extern crate rand;
+
+mod common;
+
+use common::{identical, random_values};
+use float::Float;
+use rand::Rng;
+
+fn values() -> Vec<Float> {
+    let specials = vec![Float::zero(1), Float::neg_zero(7), Float::infinity(2),
+                        Float::neg_infinity(100), Float::nan(53), Float::nan(u32::max_value()),
+                        Float::max(64), Float::min(64), Float::min_positive(200),
+                        Float::from(1).with_precision(1000).next_above(),
+                        Float::from(-1).with_precision(1000).next_below()];
+    random_values(specials, &[1, 2, 7, 8, 9, 53, 64, 65, 300],
+                  |rng| rng.gen::<i64>() >> rng.gen_range(0, 64))
+}
+
+#[test]
+fn round_trip() {
+    for x in values() {
+        let b = x.to_bytes();
+        let y = Float::from_bytes(&b).unwrap();
+        assert!(identical(&x, &y), "{:?} {:?} {:?}", x, b, y);
+        assert_eq!(y.to_bytes(), b);
+    }
+}
+
+#[test]
+fn layout() {
+    assert_eq!(Float::from(1.5).to_bytes(), [1, 53, 0, 3]);
+    assert_eq!(Float::from(-1.5).with_precision(2).to_bytes(), [5, 2, 0, 3]);
+    assert_eq!(Float::from(0.75).with_precision(200).to_bytes(), [1, 0xc8, 1, 1, 3]);
+    assert_eq!(Float::from(1u64 << 63).with_precision(300).to_bytes(), [1, 0xac, 2, 0x7e, 1]);
+    assert_eq!(Float::from(0xabcd).to_bytes(), [1, 16, 30, 0xcd, 0xab]);
+    assert_eq!(Float::from(1).mul_exp2(-65).to_bytes(), [1, 1, 0x81, 1, 1]);
+    assert_eq!(Float::neg_zero(3).to_bytes(), [4, 3]);
+    assert_eq!(Float::zero(3).to_bytes(), [0, 3]);
+    assert_eq!(Float::infinity(3).to_bytes(), [2, 3]);
+    assert_eq!(Float::neg_infinity(3).to_bytes(), [6, 3]);
+    assert_eq!(Float::nan(3).to_bytes()[1..], [3]);
+}
+
+#[test]
+fn invalid() {
+    let invalid: &[&[u8]] = &[
+        &[],
+        &[0],
+        // zero precision
+        &[0, 0],
+        // precision too large
+        &[0, 0x80, 0x80, 0x80, 0x80, 0x10],
+        // redundant varint bytes
+        &[0, 0x83, 0],
+        &[1, 53, 0x80, 0, 3],
+        // unfinished varint
+        &[1, 0x83],
+        // unknown tag
+        &[8, 3],
+        &[0x81, 3],
+        // trailing bytes
+        &[0, 3, 0],
+        &[2, 3, 1],
+        // missing significand
+        &[1, 53, 0],
+        // even significand
+        &[1, 53, 0, 2],
+        // trailing zero byte
+        &[1, 53, 0, 3, 0],
+        // too many bits for the precision
+        &[1, 1, 0, 3],
+        &[1, 8, 0, 1, 1],
+        // exponent too large
+        &[1, 53, 0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01, 1],
+        &[1, 53, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01, 1],
+        &[1, 53, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x02, 1],
+    ];
+    for b in invalid {
+        assert!(Float::from_bytes(b).is_none(), "{:?}", b);
+    }
+}
+
+#[test]
+fn sortable_round_trip() {
+    for x in values() {
+        let b = x.to_sortable_bytes();
+        let y = Float::from_sortable_bytes(&b).unwrap();
+        assert!(identical(&x, &y), "{:?} {:?} {:?}", x, b, y);
+        assert_eq!(y.to_sortable_bytes(), b);
+    }
+}
+
+#[test]
+fn sortable_order() {
+    let mut v = values();
+    // the same values at different precisions, and some neighbours
+    let extra: Vec<_> = v.iter().flat_map(|x| {
+        vec![x.clone().with_precision(x.precision() + 10),
+             -x.clone(),
+             x.clone().next_above(),
+             x.clone().next_below()]
+    }).collect();
+    v.extend(extra);
+    v.push(Float::nan(1));
+    v.push(-Float::nan(1000));
+
+    let mut sorted: Vec<_> = v.iter().map(|x| (x.to_sortable_bytes(), x)).collect();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+    for w in sorted.windows(2) {
+        let (x, y) = (w[0].1, w[1].1);
+        match (x.sign().is_none(), y.sign().is_none()) {
+            (false, false) => assert!(x <= y, "{:?} {:?}", x, y),
+            (true, false) => panic!("{:?} before {:?}", x, y),
+            _ => {}
+        }
+    }
+    // equal values are adjacent
+    for w in sorted.windows(3) {
+        assert!(!(w[0].1 == w[2].1 && w[0].1 != w[1].1));
+    }
+
+    let zeros = [Float::neg_zero(1), Float::neg_zero(2), Float::zero(1), Float::zero(2)];
+    for w in zeros.windows(2) {
+        assert!(w[0].to_sortable_bytes() < w[1].to_sortable_bytes());
+    }
+}
+
+#[test]
+fn sortable_invalid() {
+    let valid = Float::from(-1.5).with_precision(20).to_sortable_bytes();
+    assert_eq!(valid, [2, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                       0x7f, 0xff, 0xff, 0, 0, 0, 20]);
+    let invalid: &[&[u8]] = &[
+        &[],
+        &[3, 0, 0, 0, 1],
+        // zero precision
+        &[3, 1, 0, 0, 0, 0],
+        // unknown class
+        &[0, 0, 0, 0, 1],
+        &[7, 0, 0, 0, 1],
+        // bad sign byte
+        &[3, 2, 0, 0, 0, 1],
+        // trailing bytes
+        &[1, 0, 0, 0, 0, 1],
+        // missing terminator
+        &[4, 0x80, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1],
+        // bad escape
+        &[4, 0x80, 0, 0, 0, 0, 0, 0, 0, 0x80, 0, 1, 0, 0, 0, 0, 20],
+        // trailing zero byte in the significand
+        &[4, 0x80, 0, 0, 0, 0, 0, 0, 0, 0x80, 0, 0xff, 0, 0, 0, 0, 0, 20],
+        // data after the terminator
+        &[4, 0x80, 0, 0, 0, 0, 0, 0, 0, 0x80, 0, 0, 1, 0, 0, 0, 20],
+        // too many bits for the precision
+        &[4, 0x80, 0, 0, 0, 0, 0, 0, 0, 0x80, 0, 0, 0, 0, 0, 1],
+        // exponent too large
+        &[4, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0, 0, 1],
+    ];
+    for b in invalid {
+        assert!(Float::from_sortable_bytes(b).is_none(), "{:?}", b);
+    }
+    assert!(Float::from_sortable_bytes(&valid).is_some());
+}
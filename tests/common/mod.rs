@@ -0,0 +1,51 @@
+// Helpers shared by several of the tests, which each include them
+// with `mod common;`, so not every test uses all of them.
+#![allow(dead_code)]
+
+extern crate rand;
+
+use float::Float;
+use self::rand::Rng;
+
+use std::mem;
+
+// the same value with the same sign.
+pub fn same(x: &Float, y: &Float) -> bool {
+    x == y && x.sign() == y.sign()
+}
+
+// the same value with the same precision and sign, even for NaN
+// (whose sign is only visible through `Debug`).
+pub fn identical(x: &Float, y: &Float) -> bool {
+    x.precision() == y.precision() && x.sign() == y.sign() &&
+        (x == y || x.sign().is_none()) && format!("{:?}", x) == format!("{:?}", y)
+}
+
+// each of `xs` rounded to each of the precisions `precs`.
+pub fn at_precisions(xs: &[f64], precs: &[u32]) -> Vec<Float> {
+    let mut v = vec![];
+    for &x in xs {
+        for &prec in precs {
+            v.push(Float::from(x).with_precision(prec));
+        }
+    }
+    v
+}
+
+// `v` followed by up to 100 random finite values at each of the
+// precisions `precs`, each multiplied by `2**exp(rng)`.
+pub fn random_values<F>(mut v: Vec<Float>, precs: &[u32], mut exp: F) -> Vec<Float>
+    where F: FnMut(&mut rand::ThreadRng) -> i64
+{
+    let mut rng = rand::thread_rng();
+    for &prec in precs {
+        for _ in 0..100 {
+            let x: f64 = unsafe { mem::transmute(rng.gen::<u64>()) };
+            if x.is_finite() {
+                let e = exp(&mut rng);
+                v.push(Float::from(x).with_precision(prec).mul_exp2(e));
+            }
+        }
+    }
+    v
+}
@@ -125,6 +125,51 @@ fn sqrt() {
           |y| y.sqrt())
 }
 
+#[test]
+fn mul_add() {
+    let p = 3;
+    floats(p, Region::NegPos, true, -3..3 + 1, |x| {
+        floats(p, Region::NegPos, true, -3..3 + 1, |y| {
+            floats(p, Region::NegPos, true, -6..6 + 1, |z| {
+                let est = x.clone().mul_add(&y, &z);
+                let (x_f64, y_f64, z_f64): (f64, f64, f64) =
+                    (x.clone().into(), y.clone().into(), z.clone().into());
+                // the exact value, rounded once by the conversion
+                let real = Float::from(x_f64 * y_f64 + z_f64).with_precision(p);
+                assert!(est == real && est.sign() == real.sign(),
+                        "{:?}, {:?}, {:?} => {:?} != {:?}",
+                        x, y, z, est, real);
+            })
+        })
+    })
+}
+
+#[test]
+fn fmma_fmms() {
+    let p = 2;
+    floats(p, Region::NegPos, true, -2..2 + 1, |x| {
+        floats(p, Region::NegPos, true, -2..2 + 1, |y| {
+            floats(p, Region::NegPos, true, -2..2 + 1, |z| {
+                floats(p, Region::NegPos, true, -2..2 + 1, |w| {
+                    let (x_f64, y_f64, z_f64, w_f64): (f64, f64, f64, f64) =
+                        (x.clone().into(), y.clone().into(), z.clone().into(), w.clone().into());
+                    // the exact values, rounded once by the conversion
+                    let real_add = Float::from(x_f64 * y_f64 + z_f64 * w_f64).with_precision(p);
+                    let real_sub = Float::from(x_f64 * y_f64 - z_f64 * w_f64).with_precision(p);
+                    let est_add = x.clone().fmma(&y, &z, &w);
+                    let est_sub = x.clone().fmms(&y, &z, &w);
+                    assert!(est_add == real_add && est_add.sign() == real_add.sign(),
+                            "{:?}, {:?}, {:?}, {:?} => {:?} != {:?}",
+                            x, y, z, w, est_add, real_add);
+                    assert!(est_sub == real_sub && est_sub.sign() == real_sub.sign(),
+                            "{:?}, {:?}, {:?}, {:?} => {:?} != {:?}",
+                            x, y, z, w, est_sub, real_sub);
+                })
+            })
+        })
+    })
+}
+
 macro_rules! expr { ($e: expr) => { $e } }
 macro_rules! by_val_by_ref {
     ($op: tt, $tester: ident) => {
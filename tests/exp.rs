@@ -0,0 +1,193 @@
+extern crate float;
+
+mod common;
+
+use common::{same, at_precisions};
+use float::{Float, Exception, RoundingMode};
+
+use std::cmp::Ordering;
+
+const E: &'static str = "2.71828182845904523536028747135266249775724709369995957496696762772";
+
+fn values() -> Vec<Float> {
+    at_precisions(&[1.0, -1.0, 0.5, 3.0, -7.25, 1e-3, -1e-3, 1e-20, 100.0, -100.0,
+                    0.693147180559945, 12345.678, -2000.0],
+                  &[2, 10, 53, 100])
+}
+
+#[test]
+fn special() {
+    let nearest = RoundingMode::NearestEven;
+    assert!(Float::nan(10).exp().sign().is_none());
+    assert!(same(&Float::infinity(10).exp(), &Float::infinity(10)));
+    assert!(same(&Float::neg_infinity(10).exp(), &Float::zero(10)));
+    assert_eq!(Float::zero(10).exp_ternary(nearest), (Float::from(1), Ordering::Equal));
+    assert_eq!(Float::neg_zero(10).exp_ternary(nearest), (Float::from(1), Ordering::Equal));
+
+    assert!(Float::nan(10).expm1().sign().is_none());
+    assert!(same(&Float::infinity(10).expm1(), &Float::infinity(10)));
+    assert!(same(&Float::neg_infinity(10).expm1(), &Float::from(-1)));
+    assert!(same(&Float::zero(10).expm1(), &Float::zero(10)));
+    assert!(same(&Float::neg_zero(10).expm1(), &Float::neg_zero(10)));
+
+    for &(_, f) in &[("exp2", Float::exp2_ternary as fn(Float, RoundingMode) -> _),
+                     ("exp10", Float::exp10_ternary)] {
+        assert!(f(Float::nan(10), nearest).0.sign().is_none());
+        assert!(same(&f(Float::infinity(10), nearest).0, &Float::infinity(10)));
+        assert!(same(&f(Float::neg_infinity(10), nearest).0, &Float::zero(10)));
+        assert_eq!(f(Float::zero(10), nearest), (Float::from(1), Ordering::Equal));
+        assert_eq!(f(Float::neg_zero(10), nearest), (Float::from(1), Ordering::Equal));
+    }
+}
+
+#[test]
+fn e() {
+    for &prec in &[1, 2, 24, 53, 64, 200] {
+        let e = Float::from(1).with_precision(prec).exp();
+        let want = Float::from_str_with_precision(E, prec, RoundingMode::NearestEven).unwrap();
+        assert_eq!(e, want);
+        let e_m1 = Float::from(1).with_precision(prec).expm1();
+        let want = Float::from_str_with_precision(&E.replace("2.", "1."), prec,
+                                                  RoundingMode::NearestEven).unwrap();
+        assert_eq!(e_m1, want);
+    }
+}
+
+#[test]
+fn correctly_rounded() {
+    let (down, up) = (RoundingMode::TowardNegative, RoundingMode::TowardPositive);
+    for x in values() {
+        let prec = x.precision();
+        let mut fs = vec![("exp", Float::exp_ternary as fn(Float, RoundingMode) -> _),
+                          ("expm1", Float::expm1_ternary)];
+        // integers are handled by `exact_powers`
+        let f: f64 = x.clone().into();
+        if f.fract() != 0.0 {
+            fs.push(("exp2", Float::exp2_ternary));
+            fs.push(("exp10", Float::exp10_ternary));
+        }
+        for &(ref name, f) in &fs {
+            let (lo, lo_ord) = f(x.clone(), down);
+            let (hi, hi_ord) = f(x.clone(), up);
+            assert!(lo_ord == Ordering::Less && hi_ord == Ordering::Greater, "{} {:?}", name, x);
+            assert_eq!(lo.clone().next_above(), hi);
+
+            // the nearest is whichever bound is closer to a more
+            // precise result.
+            let (nearest, _) = f(x.clone(), RoundingMode::NearestEven);
+            let precise = f(x.clone().with_precision(prec + 100), RoundingMode::NearestEven).0;
+            let mid = Float::add_to(&lo, &hi, prec + 1, RoundingMode::NearestEven).mul_exp2(-1);
+            let want = if precise < mid { lo } else { hi };
+            assert_eq!(nearest, want, "{} {:?}", name, x);
+        }
+    }
+}
+
+#[test]
+fn expm1_tiny() {
+    for &prec in &[2, 10, 53, 100] {
+        for &e in &[-200, -1 << 40, -(1 << 62)] {
+            let x = Float::from(1).with_precision(prec).mul_exp2(e);
+            assert_eq!(x.clone().expm1(), x);
+            assert_eq!(x.clone().expm1_round(RoundingMode::TowardPositive),
+                       x.clone().next_above());
+            let y = -x.clone();
+            assert_eq!(y.clone().expm1(), y);
+            assert_eq!(y.clone().expm1_round(RoundingMode::TowardPositive),
+                       y.clone().next_above());
+            assert_eq!(x.clone().exp(), 1);
+            assert_eq!(y.clone().exp_round(RoundingMode::TowardNegative),
+                       Float::from(1).with_precision(prec).next_below());
+        }
+        let x = Float::min_positive(prec);
+        assert_eq!(x.clone().expm1(), x);
+        assert_eq!((-x.clone()).expm1(), -x);
+    }
+}
+
+#[test]
+fn out_of_range() {
+    Float::clear_flags();
+    assert_eq!(Float::from(1).mul_exp2(63).exp(), Float::infinity(53));
+    assert!(Float::flags().contains(Exception::Overflow));
+    Float::clear_flags();
+    assert!(same(&Float::from(-1).mul_exp2(63).exp(), &Float::zero(53)));
+    assert!(Float::flags().contains(Exception::Underflow));
+    assert_eq!(Float::from(-1).mul_exp2(63).expm1(), -1);
+    assert!(Float::from(-1).mul_exp2(63).expm1_round(RoundingMode::TowardZero) > -1);
+
+    // e**(2**62) is about 2**(1.44 * 2**62), which is in range.
+    let big = Float::from(1).mul_exp2(62).exp();
+    assert!(big < Float::infinity(53) && big.mul_exp2(-(1 << 62)) > 1);
+    let small = Float::from(-1).mul_exp2(62).exp();
+    assert!(small > 0 && small.mul_exp2(1 << 62) < 1);
+}
+
+#[test]
+fn binary64_range() {
+    Float::set_exp_range(-1022, 1023);
+    Float::clear_flags();
+    assert_eq!(Float::from(709.782712893384).exp(), Float::from(1.7976931348622732e308));
+    assert!(!Float::flags().contains(Exception::Overflow));
+    assert_eq!(Float::from(709.79).exp(), Float::infinity(53));
+    assert!(Float::flags().contains(Exception::Overflow));
+
+    Float::clear_flags();
+    let x = Float::from(-745.0).exp();
+    assert!(Float::flags().contains(Exception::Underflow));
+    assert_eq!(Float::from(-746.0).exp(), 0);
+    let y = Float::from(-746.0).exp_round(RoundingMode::TowardPositive);
+    Float::reset_exp_range();
+    let min = Float::from(1).mul_exp2(-1074);
+    assert_eq!(x, min);
+    assert_eq!(y, min);
+}
+
+#[test]
+fn exact_powers() {
+    let nearest = RoundingMode::NearestEven;
+    let one = Float::from(1).with_precision(53);
+    for &n in &[-1074, -3, -1, 1, 10, 1023, 1 << 40, -(1 << 62), (1 << 62) + 1] {
+        let (x, ord) = Float::from(n).with_precision(64).exp2_ternary(nearest);
+        assert_eq!((x, ord), (one.clone().mul_exp2(n), Ordering::Equal));
+    }
+    let mut power = 1.0;
+    Float::clear_flags();
+    for n in 0..23 {
+        // 10**n is exact in an f64 for n <= 22
+        let (x, ord) = Float::from(n).with_precision(53).exp10_ternary(nearest);
+        assert_eq!((x, ord), (Float::from(power), Ordering::Equal));
+        power *= 10.0;
+    }
+    assert!(!Float::flags().contains(Exception::Inexact));
+    for &(n, power) in &[(23, 1e23), (100, 1e100), (308, 1e308), (-1, 0.1), (-22, 1e-22),
+                         (-300, 1e-300)] {
+        let (x, ord) = Float::from(n).with_precision(53).exp10_ternary(nearest);
+        assert!(x == Float::from(power) && ord != Ordering::Equal, "{}", n);
+    }
+    // 5**100 fits in 233 bits
+    let (x, ord) = Float::from(100).with_precision(233).exp10_ternary(nearest);
+    assert_eq!(ord, Ordering::Equal);
+    assert_eq!(x, Float::from_str_with_precision("1e100", 233, nearest).unwrap());
+}
+
+#[test]
+fn exp2_exp10_range() {
+    Float::clear_flags();
+    assert_eq!(Float::from(1).mul_exp2(63).exp2(), Float::infinity(53));
+    assert!(Float::flags().contains(Exception::Overflow));
+    assert_eq!(Float::from(1).mul_exp2(62).exp10(), Float::infinity(53));
+
+    Float::set_exp_range(-1022, 1023);
+    Float::clear_flags();
+    assert_eq!(Float::from(1024).with_precision(53).exp2(), Float::infinity(53));
+    assert_eq!(Float::from(1024).with_precision(53).exp2_round(RoundingMode::TowardZero),
+               Float::from(1.7976931348623157e308));
+    assert!(Float::flags().contains(Exception::Overflow));
+    let tiny = Float::from(-1074).with_precision(53).exp2();
+    assert_eq!(Float::from(309).with_precision(53).exp10(), Float::infinity(53));
+    let small = Float::from(-320).with_precision(53).exp10();
+    Float::reset_exp_range();
+    assert_eq!(tiny, Float::from(1).mul_exp2(-1074));
+    assert_eq!(small, Float::from(1e-320));
+}
@@ -0,0 +1,161 @@
+extern crate float;
+
+use float::{Float, Exception, RoundingMode};
+
+use std::{f32, f64};
+
+// the exact value of `x`, which may be subnormal, computed without
+// any restriction on the exponent range.
+fn exact(x: f64) -> Float {
+    Float::reset_exp_range();
+    Float::from(x * 2f64.powi(600)).mul_exp2(-600)
+}
+
+fn binary64() {
+    Float::set_exp_range(-1022, 1023);
+}
+
+#[test]
+fn extremes() {
+    binary64();
+    assert_eq!(Float::max(53), Float::from(f64::MAX));
+    assert_eq!(Float::min_positive(53), Float::from(f64::MIN_POSITIVE));
+    binary64();
+    assert_eq!(Float::zero(53).next_above(), exact(5e-324));
+    binary64();
+    assert_eq!(Float::neg_zero(53).next_below(), exact(-5e-324));
+    binary64();
+    assert_eq!(Float::max(53).next_above(), Float::infinity(53));
+    assert_eq!(Float::infinity(53).next_below(), Float::max(53));
+    Float::reset_exp_range();
+}
+
+#[test]
+fn next_above_subnormal() {
+    let mut x = 0.0f64;
+    binary64();
+    let mut f = Float::zero(53);
+    for _ in 0..1000 {
+        let next = x + 5e-324;
+        let g = f.clone().next_above();
+        let want = exact(next);
+        binary64();
+        assert_eq!(g, want);
+        assert_eq!(g.clone().next_below(), f);
+        x = next;
+        f = g;
+    }
+
+    let mut x = f64::MIN_POSITIVE - 500.0 * 5e-324;
+    let mut f = exact(x);
+    binary64();
+    for _ in 0..1000 {
+        let next = x + 5e-324;
+        let g = f.clone().next_above();
+        let want = exact(next);
+        binary64();
+        assert_eq!(g, want);
+        assert_eq!(g.clone().next_below(), f);
+        x = next;
+        f = g;
+    }
+    Float::reset_exp_range();
+}
+
+#[test]
+fn gradual_underflow() {
+    let xs = [1.5e-160, -3.7e-163, 1.1e-154, 7.9e-165, -1.0e-170];
+    let ys = [2.3e-150, 1.9e-146, -6.1e-160, 4.4e-159, 3.3e-140];
+    for &x in &xs {
+        for &y in &ys {
+            let (fx, fy) = (exact(x), exact(y));
+            let want = exact(x * y);
+            binary64();
+            Float::clear_flags();
+            let got = &fx * &fy;
+            assert_eq!(got, want, "{:e} * {:e}", x, y);
+            assert_eq!(got.sign(), want.sign());
+            if x * y != 0.0 && (x * y).abs() < f64::MIN_POSITIVE {
+                assert!(Float::flags().contains(Exception::Underflow));
+            }
+        }
+    }
+    Float::reset_exp_range();
+}
+
+#[test]
+fn subnormal_exact() {
+    // subtracting nearby normals gives an exact subnormal, with no
+    // underflow signalled.
+    let x = f64::MIN_POSITIVE * 1.5;
+    let y = f64::MIN_POSITIVE * 1.25;
+    let want = exact(x - y);
+    binary64();
+    Float::clear_flags();
+    assert_eq!(Float::from(x) - Float::from(y), want);
+    assert!(Float::flags().is_empty());
+    Float::reset_exp_range();
+}
+
+#[test]
+fn directed() {
+    let tiny = Float::from(f64::MIN_POSITIVE).mul_exp2(-60);
+    binary64();
+    assert_eq!(tiny.clone().with_precision_round(53, RoundingMode::TowardZero),
+               Float::zero(53));
+    let up = tiny.clone().with_precision_round(53, RoundingMode::TowardPositive);
+    assert_eq!(up, Float::zero(53).next_above());
+    assert_eq!((-tiny).with_precision_round(53, RoundingMode::TowardNegative),
+               Float::zero(53).next_below());
+
+    let big = Float::max(53);
+    assert_eq!(big.clone().mul_round(&Float::from(2.0), RoundingMode::TowardZero),
+               Float::max(53));
+    assert_eq!(big.mul_round(&Float::from(2.0), RoundingMode::NearestEven),
+               Float::infinity(53));
+    Float::reset_exp_range();
+}
+
+#[test]
+fn to_f64_round() {
+    // 1.5 and 1.25 times the smallest subnormal, and the expected
+    // results for them and their negations.
+    let tie = Float::from(1.5).mul_exp2(-1074);
+    let below = Float::from(1.25).mul_exp2(-1074);
+    let (a, b) = (5e-324, 1e-323);
+    let expected = [(RoundingMode::NearestEven, b, -b, a, -a),
+                    (RoundingMode::NearestAway, b, -b, a, -a),
+                    (RoundingMode::TowardZero, a, -a, a, -a),
+                    (RoundingMode::TowardPositive, b, -a, b, -a),
+                    (RoundingMode::TowardNegative, a, -b, a, -b)];
+    for &(mode, t, neg_t, b, neg_b) in &expected {
+        assert_eq!(tie.clone().to_f64_round(mode), t);
+        assert_eq!((-tie.clone()).to_f64_round(mode), neg_t);
+        assert_eq!(below.clone().to_f64_round(mode), b);
+        assert_eq!((-below.clone()).to_f64_round(mode), neg_b);
+    }
+
+    let huge = Float::max(53);
+    assert_eq!(huge.clone().to_f64_round(RoundingMode::NearestEven), f64::INFINITY);
+    assert_eq!(huge.clone().to_f64_round(RoundingMode::TowardZero), f64::MAX);
+    assert_eq!((-huge).to_f64_round(RoundingMode::TowardPositive), f64::MIN);
+
+    let small = Float::min_positive(53);
+    assert_eq!(small.clone().to_f64_round(RoundingMode::NearestEven), 0.0);
+    assert_eq!(small.clone().to_f64_round(RoundingMode::TowardPositive), 5e-324);
+    assert!((-small).to_f64_round(RoundingMode::TowardZero).is_sign_negative());
+}
+
+#[test]
+fn to_f32_round() {
+    let x = Float::from(1.0f32 + 2f32.powi(-23)).with_precision(30).mul_exp2(-140);
+    assert_eq!(x.clone().to_f32_round(RoundingMode::NearestEven), 2f32.powi(-140));
+    assert_eq!(x.clone().to_f32_round(RoundingMode::TowardPositive),
+               2f32.powi(-140) + 2f32.powi(-149));
+    assert_eq!(Float::from(f32::MIN_POSITIVE).mul_exp2(-24)
+                   .to_f32_round(RoundingMode::NearestEven),
+               0.0);
+    assert_eq!(Float::from(f32::MAX).with_precision(30).next_above()
+                   .to_f32_round(RoundingMode::TowardZero),
+               f32::MAX);
+}
@@ -0,0 +1,124 @@
+extern crate float;
+
+use float::{Float, Exception, Flags};
+
+use std::cell::Cell;
+use std::panic;
+use std::rc::Rc;
+
+fn raised<F: FnOnce()>(f: F) -> Flags {
+    Float::clear_flags();
+    f();
+    Float::flags()
+}
+
+fn only(e: Exception) -> Flags {
+    let mut flags = Flags::empty();
+    flags.insert(e);
+    flags
+}
+
+#[test]
+fn exact() {
+    let flags = raised(|| {
+        let x = Float::from(3.0);
+        let y = Float::from(0.5);
+        let _ = (&x + &y, &x - &y, &x * &y, &x / &y, (&x * &x).sqrt());
+    });
+    assert!(flags.is_empty(), "{:?}", flags);
+}
+
+#[test]
+fn inexact() {
+    assert_eq!(raised(|| { let _ = Float::from(1.0) / Float::from(3.0); }),
+               only(Exception::Inexact));
+    assert_eq!(raised(|| { let _ = Float::from(2.0).sqrt(); }),
+               only(Exception::Inexact));
+    assert_eq!(raised(|| { let _ = Float::from(0.1).with_precision(10); }),
+               only(Exception::Inexact));
+}
+
+#[test]
+fn overflow() {
+    let flags = raised(|| { let _ = Float::max(10) * Float::from(2).with_precision(10); });
+    assert!(flags.contains(Exception::Overflow));
+    assert!(flags.contains(Exception::Inexact));
+}
+
+#[test]
+fn underflow() {
+    let flags = raised(|| {
+        let _ = Float::min_positive(10) * Float::from(0.25).with_precision(10);
+    });
+    assert!(flags.contains(Exception::Underflow));
+    assert!(flags.contains(Exception::Inexact));
+}
+
+#[test]
+fn invalid() {
+    let inf = Float::infinity(53);
+    let zero = Float::zero(53);
+    assert_eq!(raised(|| { let _ = &inf - &inf; }), only(Exception::Invalid));
+    assert_eq!(raised(|| { let _ = &inf * &zero; }), only(Exception::Invalid));
+    assert_eq!(raised(|| { let _ = &zero / &zero; }), only(Exception::Invalid));
+    assert_eq!(raised(|| { let _ = &inf / &inf; }), only(Exception::Invalid));
+    assert_eq!(raised(|| { let _ = Float::from(-1.0).sqrt(); }), only(Exception::Invalid));
+
+    // NaN inputs are quiet
+    assert!(raised(|| { let _ = Float::nan(53) + Float::from(1.0); }).is_empty());
+}
+
+#[test]
+fn divide_by_zero() {
+    assert_eq!(raised(|| { let _ = Float::from(1.0) / Float::zero(53); }),
+               only(Exception::DivideByZero));
+    assert!(raised(|| { let _ = Float::infinity(53) / Float::zero(53); }).is_empty());
+
+    // the signs of the results are those of the operands multiplied
+    let (zero, neg_zero) = (Float::zero(53), Float::neg_zero(53));
+    let (inf, neg_inf) = (Float::infinity(53), Float::neg_infinity(53));
+    let same = |x: Float, y: &Float| x == *y && x.sign() == y.sign();
+    assert!(same(Float::from(-1.0) / &zero, &neg_inf));
+    assert!(same(Float::from(1.0) / &neg_zero, &neg_inf));
+    assert!(same(Float::from(-1.0) / &neg_zero, &inf));
+    assert!(same(neg_inf.clone() / &zero, &neg_inf));
+    assert!(same(inf.clone() / &neg_zero, &neg_inf));
+    assert!(same(zero.clone() / Float::from(-1.0), &neg_zero));
+    assert!(same(neg_zero.clone() / Float::from(-1.0), &zero));
+    assert!(same(Float::from(1.0) / &neg_inf, &neg_zero));
+    assert!(same(Float::from(-1.0) / &neg_inf, &zero));
+}
+
+#[test]
+fn trap() {
+    let count = Rc::new(Cell::new(0));
+    let c = count.clone();
+    Float::set_trap(move |e| {
+        assert_eq!(e, Exception::Invalid);
+        c.set(c.get() + 1);
+        // doesn't recur
+        let _ = Float::zero(53) / Float::zero(53);
+    });
+    let _ = Float::zero(53) / Float::zero(53);
+    assert_eq!(count.get(), 1);
+
+    Float::clear_trap();
+    let _ = Float::zero(53) / Float::zero(53);
+    assert_eq!(count.get(), 1);
+}
+
+#[test]
+fn panicking_trap() {
+    let count = Rc::new(Cell::new(0));
+    let c = count.clone();
+    Float::set_trap(move |_| {
+        c.set(c.get() + 1);
+        panic!("trapped")
+    });
+    for _ in 0..2 {
+        let result = panic::catch_unwind(|| Float::zero(53) / Float::zero(53));
+        assert!(result.is_err());
+    }
+    Float::clear_trap();
+    assert_eq!(count.get(), 2);
+}
@@ -0,0 +1,24 @@
+extern crate float;
+
+mod common;
+
+use common::same;
+use float::{Float, RoundingMode};
+
+#[test]
+fn fmms_cancellation() {
+    let max = Float::max(20);
+    let tiny = Float::min_positive(20);
+    let (zero, neg_zero) = (Float::zero(20), Float::neg_zero(20));
+    // an exact zero is +0, or -0 when rounding down, even when the
+    // products are far outside the exponent range.
+    for x in &[Float::from(3), max.clone(), tiny.clone()] {
+        assert!(same(&x.clone().fmms(&max, x, &max), &zero), "{:?}", x);
+        let diff = x.clone().fmms_round(&tiny, x, &tiny, RoundingMode::TowardNegative);
+        assert!(same(&diff, &neg_zero), "{:?}", x);
+    }
+    // neither product is representable, but their difference is
+    let four = Float::from(4).with_precision(20);
+    let diff = max.clone().fmms(&four, &max, &four.clone().next_below());
+    assert_eq!(diff, max.clone().mul_exp2(-18));
+}
@@ -0,0 +1,336 @@
+extern crate float;
+extern crate rand;
+
+use float::{Float, RoundingMode};
+use rand::Rng;
+
+use std::mem;
+
+// random finite values spread across the whole range of f64, along
+// with some more ordinary ones.
+fn values() -> Vec<f64> {
+    let mut rng = rand::thread_rng();
+    let mut v = vec![0.0, -0.0, 1.0, -1.0, 0.1, 0.5, 1.5, 2.5, 9.5, 99.5, 123.456,
+                     1e300, 1e-300, 5e-324, 2.2250738585072014e-308,
+                     1.7976931348623157e308];
+    while v.len() < 2000 {
+        let x: f64 = unsafe { mem::transmute(rng.gen::<u64>()) };
+        if x.is_finite() {
+            v.push(x);
+        }
+        let y = rng.gen_range(-1e6, 1e6) * 10f64.powi(rng.gen_range(-20, 20));
+        v.push(y);
+    }
+    v
+}
+
+#[test]
+fn exp_matches_f64() {
+    for x in values() {
+        let f = Float::from(x);
+        for digits in 0..25 {
+            assert_eq!(format!("{:.*e}", digits, f), format!("{:.*e}", digits, x));
+            assert_eq!(format!("{:.*E}", digits, f), format!("{:.*E}", digits, x));
+        }
+    }
+}
+
+#[test]
+fn fixed_matches_f64() {
+    for x in values() {
+        let f = Float::from(x);
+        for digits in 0..25 {
+            assert_eq!(format!("{:.*}", digits, f), format!("{:.*}", digits, x));
+        }
+    }
+}
+
+#[test]
+fn flags_and_width() {
+    for x in values() {
+        let f = Float::from(x);
+        assert_eq!(format!("{:+.3e}", f), format!("{:+.3e}", x));
+        assert_eq!(format!("{:015.2e}", f), format!("{:015.2e}", x));
+        assert_eq!(format!("{:>20.4}", f), format!("{:>20.4}", x));
+        assert_eq!(format!("{:*^+20.1}", f), format!("{:*^+20.1}", x));
+    }
+}
+
+#[test]
+fn round_trip() {
+    // without a precision, there are enough digits to get back the
+    // same f64.
+    for x in values() {
+        let f = Float::from(x);
+        assert_eq!(format!("{}", f).parse::<f64>().unwrap(), x);
+        assert_eq!(format!("{:e}", f).parse::<f64>().unwrap(), x);
+    }
+}
+
+#[test]
+fn default_digits() {
+    assert_eq!(format!("{}", Float::from(1.5)), "1.5");
+    assert_eq!(format!("{}", Float::from(100)), "100");
+    assert_eq!(format!("{}", Float::from(-0.0)), "-0");
+    assert_eq!(format!("{}", Float::from(0.1)), "0.10000000000000001");
+    assert_eq!(format!("{:e}", Float::from(0.1)), "1.0000000000000001e-1");
+    assert_eq!(format!("{:e}", Float::from(0.1).with_precision(10)), "9.9976e-2");
+    assert_eq!(format!("{:e}", Float::from(1500)), "1.5e3");
+}
+
+#[test]
+fn special() {
+    for &(ref x, s) in &[(Float::infinity(10), "inf"), (Float::neg_infinity(10), "-inf"),
+                     (Float::nan(10), "NaN")] {
+        assert_eq!(format!("{}", x), s);
+        assert_eq!(format!("{:e}", x), s);
+        assert_eq!(format!("{:.3E}", x), s);
+    }
+    assert_eq!(format!("{:+}", Float::infinity(10)), "+inf");
+    assert_eq!(format!("{:>5}", Float::nan(10)), "  NaN");
+}
+
+#[test]
+fn huge_exponents() {
+    let x = Float::from(1).with_precision(64).mul_exp2(1 << 40);
+    assert_eq!(format!("{:.20e}", x), "8.05723224506582382563e330985980541");
+    let x = Float::from(3).with_precision(64).mul_exp2(-(1 << 50));
+    assert_eq!(format!("{:.20e}", x), "3.48961867139819334916e-338929644074912");
+    assert_eq!(format!("{:.3}", x), "0.000");
+}
+
+#[test]
+fn shortest_matches_f64() {
+    // f64's formatting gives the shortest round-tripping digits, with
+    // the binary64 range for subnormals.
+    Float::set_exp_range(-1022, 1023);
+    let mut rng = rand::thread_rng();
+    let mut v = values();
+    for _ in 0..500 {
+        let x: f64 = unsafe { mem::transmute(rng.gen::<u64>() >> 12) };
+        v.push(x);
+    }
+    for x in v {
+        assert_eq!(Float::from(x).to_shortest_decimal(), format!("{:e}", x));
+    }
+    Float::reset_exp_range();
+}
+
+#[test]
+fn shortest_precisions() {
+    let mut rng = rand::thread_rng();
+    for &prec in &[1, 2, 5, 24, 64, 113, 200] {
+        for _ in 0..200 {
+            let x: f64 = unsafe { mem::transmute(rng.gen::<u64>()) };
+            if !x.is_finite() {
+                continue
+            }
+            let f = Float::from(x).with_precision(prec)
+                .mul_exp2(rng.gen_range(-1 << 40, 1 << 40));
+            let s = f.to_shortest_decimal();
+            let back = Float::from_str_with_precision(&s, prec, RoundingMode::NearestEven);
+            assert_eq!(back.unwrap(), f);
+
+            // the nearest value with one fewer digit doesn't round trip.
+            let digits = s.trim_left_matches('-').find('e').unwrap() - 1;
+            if digits > 1 {
+                let shorter = format!("{:.*e}", digits - 2, f);
+                let back = Float::from_str_with_precision(&shorter, prec,
+                                                          RoundingMode::NearestEven);
+                assert!(back.unwrap() != f, "{} {}", s, shorter);
+            }
+        }
+    }
+}
+
+#[test]
+fn shortest_special() {
+    assert_eq!(Float::from(0.1).to_shortest_decimal(), "1e-1");
+    assert_eq!(Float::from(0.1).with_precision(10).to_shortest_decimal(), "1e-1");
+    assert_eq!(Float::from(1).with_precision(200).mul_exp2(-1).to_shortest_decimal(), "5e-1");
+    assert_eq!(Float::from(-1500).to_shortest_decimal(), "-1.5e3");
+    assert_eq!(Float::from(2.0 / 3.0).with_precision(4).to_shortest_decimal(), "7e-1");
+    assert_eq!(Float::zero(10).to_shortest_decimal(), "0e0");
+    assert_eq!(Float::neg_zero(10).to_shortest_decimal(), "-0e0");
+    assert_eq!(Float::infinity(10).to_shortest_decimal(), "inf");
+    assert_eq!(Float::neg_infinity(10).to_shortest_decimal(), "-inf");
+    assert_eq!(Float::nan(10).to_shortest_decimal(), "NaN");
+}
+
+#[test]
+fn shortest_outside_range() {
+    // values outside the current exponent range are formatted as if
+    // there were no restriction, and quietly.
+    let big = Float::from(1).with_precision(53).mul_exp2(2000);
+    let tiny = Float::from(1.5).mul_exp2(-1100);
+    Float::set_exp_range(-1022, 1023);
+    Float::clear_flags();
+    let (b, t) = (big.to_shortest_decimal(), tiny.to_shortest_decimal());
+    let flags = Float::flags();
+    Float::reset_exp_range();
+    assert!(flags.is_empty(), "{:?}", flags);
+    let nearest = RoundingMode::NearestEven;
+    assert_eq!(Float::from_str_with_precision(&b, 53, nearest).unwrap(), big);
+    assert_eq!(Float::from_str_with_precision(&t, 53, nearest).unwrap(), tiny);
+}
+
+#[test]
+fn exact_decimal() {
+    assert_eq!(Float::from(0.1).exact_decimal().to_string(),
+               "0.1000000000000000055511151231257827021181583404541015625");
+    assert_eq!(Float::from(-1.5).exact_decimal().to_string(), "-1.5");
+    assert_eq!(Float::from(1u64 << 63).exact_decimal().to_string(), "9223372036854775808");
+    assert_eq!(Float::from(1e23).exact_decimal().to_string(), "99999999999999991611392");
+    assert_eq!(Float::neg_zero(10).exact_decimal().to_string(), "-0");
+    assert_eq!(Float::neg_infinity(10).exact_decimal().to_string(), "-inf");
+    assert_eq!(Float::nan(10).exact_decimal().to_string(), "NaN");
+
+    let tiny = Float::from(5e-324).exact_decimal().to_string();
+    assert!(tiny.starts_with("0.000"));
+    assert!(tiny.ends_with("5"));
+    assert_eq!(tiny.len(), 2 + 1074);
+    assert_eq!(tiny.trim_left_matches(|c| c == '0' || c == '.').len(), 751);
+}
+
+#[test]
+fn exact_decimal_round_trip() {
+    let mut rng = rand::thread_rng();
+    for &prec in &[1, 10, 53, 100, 500] {
+        for _ in 0..100 {
+            let x: f64 = unsafe { mem::transmute(rng.gen::<u64>()) };
+            if !x.is_finite() {
+                continue
+            }
+            let f = Float::from(x).with_precision(prec).mul_exp2(rng.gen_range(-3000, 3000));
+            let s = f.exact_decimal().to_string();
+            assert_eq!(f.exact_decimal().collect::<String>(), s);
+            if s.contains('.') {
+                assert!(!s.ends_with('0'));
+            }
+            // it's exact, so rounding in either direction gives the
+            // same value.
+            for &mode in &[RoundingMode::TowardNegative, RoundingMode::TowardPositive] {
+                let back = Float::from_str_with_precision(&s, prec, mode).unwrap();
+                assert!(back == f && back.sign() == f.sign(), "{} {:?}", s, f);
+            }
+        }
+    }
+}
+
+#[test]
+fn exact_decimal_lazy() {
+    // a million digits after the point, only some of which are
+    // looked at.
+    let x = Float::from(1).mul_exp2(-1000000);
+    let mut digits = x.exact_decimal().skip(2);
+    assert!(digits.by_ref().take(301029).all(|c| c == '0'));
+    assert_eq!(digits.take(5).collect::<String>(), "10100");
+}
+
+#[test]
+fn exact_decimal_huge() {
+    // 2**(2**62) has about 1.4e18 digits, only the first few of which
+    // are generated.
+    let x = -Float::from(1).mul_exp2(1 << 62);
+    let digits = x.exact_decimal().take(41).collect::<String>();
+    assert_eq!(digits, "-1175130757822317518187382399775688517987");
+
+    let y = Float::from(1.5).mul_exp2((1 << 20) + 100);
+    let digits = y.exact_decimal().take(40).collect::<String>();
+    assert_eq!(digits, "1281811548946725311111772899744469148777");
+    // all of them, in chunks of increasing size, which have to line up
+    assert_eq!(y.exact_decimal().to_string(), format!("{:.0}", y));
+
+    // more than u32::MAX digits, many chunks in
+    let z = Float::from(3).mul_exp2(1 << 40);
+    let digits = z.exact_decimal().take(1000).collect::<String>();
+    let sci = format!("{:.1100e}", z).replace(".", "");
+    assert_eq!(digits, &sci[..1000]);
+}
+
+#[test]
+fn hex() {
+    let pi = Float::from(std::f64::consts::PI);
+    assert_eq!(format!("{:x}", pi), "0x1.921fb54442d18p+1");
+    assert_eq!(format!("{:X}", pi), "0X1.921FB54442D18P+1");
+    assert_eq!(format!("{:.3x}", pi), "0x1.922p+1");
+    assert_eq!(format!("{:.0x}", pi), "0x1p+2");
+    assert_eq!(format!("{:.15x}", pi), "0x1.921fb54442d1800p+1");
+    assert_eq!(format!("{:x}", Float::from(1)), "0x1p+0");
+    assert_eq!(format!("{:x}", Float::from(0.1)), "0x1.999999999999ap-4");
+    assert_eq!(format!("{:x}", Float::from(-5e-324)), "-0x1p-1074");
+    assert_eq!(format!("{:x}", Float::from(1.75).with_precision(3)), "0x1.cp+0");
+    assert_eq!(format!("{:x}", Float::from(1).with_precision(64).next_above()),
+               "0x1.0000000000000002p+0");
+    assert_eq!(format!("{:x}", Float::from(3).with_precision(64).mul_exp2(1 << 50)),
+               "0x1.8p+1125899906842625");
+
+    assert_eq!(format!("{:x}", Float::neg_zero(10)), "-0x0p+0");
+    assert_eq!(format!("{:.2X}", Float::zero(10)), "0X0.00P+0");
+    assert_eq!(format!("{:x}", Float::neg_infinity(10)), "-inf");
+    assert_eq!(format!("{:X}", Float::nan(10)), "NaN");
+
+    let x = Float::from(-1.5);
+    assert_eq!(format!("{:012x}", x), "-0x0001.8p+0");
+    assert_eq!(format!("{:+x}", -x.clone()), "+0x1.8p+0");
+    assert_eq!(format!("{:>12x}", x), "   -0x1.8p+0");
+    assert_eq!(format!("{:<12x}", x), "-0x1.8p+0   ");
+}
+
+#[test]
+fn radix() {
+    let third = Float::from(1.0 / 3.0);
+    assert_eq!(third.to_string_radix(3, 5, RoundingMode::TowardZero), "2.2222e-2");
+    assert_eq!(third.to_string_radix(3, 5, RoundingMode::NearestEven), "1.0000e-1");
+    assert_eq!(Float::from(-0.75).to_string_radix(2, 0, RoundingMode::NearestEven), "-1.1e-1");
+    assert_eq!(Float::from(255.5).to_string_radix(16, 0, RoundingMode::NearestEven), "f.f8@1");
+    assert_eq!(Float::from(1260).to_string_radix(36, 3, RoundingMode::NearestEven), "z.00@1");
+    assert_eq!(Float::from(1).to_string_radix(10, 0, RoundingMode::NearestEven), "1e0");
+    assert_eq!(Float::neg_zero(10).to_string_radix(7, 3, RoundingMode::NearestEven),
+               "-0.00e0");
+    assert_eq!(Float::infinity(10).to_string_radix(16, 3, RoundingMode::NearestEven), "inf");
+    assert_eq!(Float::infinity(10).to_string_radix(17, 3, RoundingMode::NearestEven), "@inf@");
+    assert_eq!(Float::nan(10).to_string_radix(36, 3, RoundingMode::NearestEven), "@NaN@");
+
+    for x in values() {
+        let f = Float::from(x);
+        for digits in 1..20 {
+            assert_eq!(f.to_string_radix(10, digits, RoundingMode::NearestEven),
+                       format!("{:.*e}", digits as usize - 1, x));
+        }
+    }
+}
+
+#[test]
+fn radix_round_trip() {
+    let mut rng = rand::thread_rng();
+    for radix in 2..37 {
+        for &prec in &[1, 7, 53, 100] {
+            for _ in 0..30 {
+                let x: f64 = unsafe { mem::transmute(rng.gen::<u64>()) };
+                if !x.is_finite() {
+                    continue
+                }
+                let f = Float::from(x).with_precision(prec)
+                    .mul_exp2(rng.gen_range(-1 << 40, 1 << 40));
+                let s = f.to_string_radix(radix, 0, RoundingMode::NearestEven);
+                let back = Float::from_str_radix(&s, radix, prec, RoundingMode::NearestEven);
+                assert_eq!(back.unwrap(), f);
+
+                // directed rounding gives bounds
+                let lo = f.to_string_radix(radix, 5, RoundingMode::TowardNegative);
+                let hi = f.to_string_radix(radix, 5, RoundingMode::TowardPositive);
+                let lo = Float::from_str_radix(&lo, radix, 64, RoundingMode::TowardNegative);
+                let hi = Float::from_str_radix(&hi, radix, 64, RoundingMode::TowardPositive);
+                assert!(lo.unwrap() <= f && f <= hi.unwrap());
+            }
+        }
+    }
+}
+
+#[test]
+fn no_flags() {
+    Float::clear_flags();
+    let _ = format!("{} {:e} {:.3}", Float::from(0.1), Float::from(1e300), Float::from(2.0 / 3.0));
+    assert!(Float::flags().is_empty());
+}
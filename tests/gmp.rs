@@ -0,0 +1,127 @@
+extern crate float;
+extern crate rand;
+
+mod common;
+
+use common::{same, random_values};
+use float::{Float, RoundingMode};
+use rand::Rng;
+
+use std::f64;
+
+fn values() -> Vec<Float> {
+    let specials = vec![Float::zero(3), Float::neg_zero(1), Float::infinity(100),
+                        Float::neg_infinity(2), Float::max(10), Float::min_positive(64)];
+    random_values(specials, &[1, 2, 24, 53, 64, 113, 200],
+                  |rng| rng.gen_range(-1 << 20, 1 << 20))
+}
+
+#[test]
+fn mpfr_output() {
+    let pi = Float::from(f64::consts::PI);
+    let nearest = RoundingMode::NearestEven;
+    assert_eq!(pi.to_mpfr_string(10, 0, nearest), "3.1415926535897931e0");
+    assert_eq!(pi.to_mpfr_string(2, 0, nearest),
+               "1.1001001000011111101101010100010001000010110100011000e1");
+    assert_eq!(pi.to_mpfr_string(16, 0, nearest), "3.243f6a8885a30@0");
+    assert_eq!(pi.to_mpfr_string(36, 4, nearest), "3.53i@0");
+    let neg_pi = Float::from(-f64::consts::PI);
+    assert_eq!(neg_pi.to_mpfr_string(10, 5, RoundingMode::TowardZero), "-3.1415e0");
+    assert_eq!(Float::from(26).to_mpfr_string(2, 4, nearest), "1.101e4");
+    assert_eq!(Float::from(0.75).to_mpfr_string(10, 1, nearest), "8e-1");
+
+    assert_eq!(Float::zero(10).to_mpfr_string(10, 0, nearest), "0");
+    assert_eq!(Float::neg_zero(10).to_mpfr_string(36, 0, nearest), "-0");
+    assert_eq!(Float::infinity(10).to_mpfr_string(10, 0, nearest), "@Inf@");
+    assert_eq!(Float::neg_infinity(10).to_mpfr_string(10, 0, nearest), "-@Inf@");
+    assert_eq!(Float::nan(10).to_mpfr_string(10, 0, nearest), "@NaN@");
+}
+
+#[test]
+fn mpfr_input() {
+    for &(s, radix, x) in &[("0.1101@5", 2, 26.0), ("1.101e4", 2, 26.0), ("-0b1.1p1", 2, -3.0),
+                            ("0B11", 2, 3.0), ("0x1.8p-3", 16, 0.1875), ("1.8P-3", 16, 0.1875),
+                            ("0x10", 16, 16.0), ("-ff@-1", 16, -15.9375), ("z.i@1", 36, 1278.0),
+                            ("1.5e-1", 10, 0.15), ("-0", 10, -0.0), ("@Inf@", 36, 1.0 / 0.0),
+                            ("-inf", 16, -1.0 / 0.0)] {
+        let f = Float::from_mpfr_str(s, radix, 53, RoundingMode::NearestEven).unwrap();
+        assert!(f == x && f.sign() == Float::from(x).sign(), "{} {}: {:?}", s, radix, f);
+    }
+    for &(s, radix) in &[("@NaN@", 10), ("nan", 16), ("@nan@", 36)] {
+        let f = Float::from_mpfr_str(s, radix, 53, RoundingMode::NearestEven).unwrap();
+        assert!(f.sign().is_none());
+    }
+    for &(s, radix) in &[("", 10), ("0x1", 10), ("1p3", 10), ("0x1p", 16), ("0x", 16),
+                         ("0b2", 2), ("0x1@1p1", 16), ("1.2.3", 10)] {
+        assert!(Float::from_mpfr_str(s, radix, 53, RoundingMode::NearestEven).is_err(),
+                "{} {}", s, radix);
+    }
+}
+
+#[test]
+fn mpfr_round_trip() {
+    for x in values() {
+        for &radix in &[2, 3, 8, 10, 16, 36] {
+            let s = x.to_mpfr_string(radix, 0, RoundingMode::NearestEven);
+            let y = Float::from_mpfr_str(&s, radix, x.precision(), RoundingMode::NearestEven);
+            let y = y.unwrap();
+            assert!(same(&x, &y), "{:?} {}: {} {:?}", x, radix, s, y);
+        }
+    }
+}
+
+#[test]
+fn mpf_output() {
+    let nearest = RoundingMode::NearestEven;
+    assert_eq!(Float::from(1.5).to_mpf_string(10, 0, nearest), "0.15e1");
+    assert_eq!(Float::from(26).to_mpf_string(2, 0, nearest), "0.1101e5");
+    assert_eq!(Float::from(-26).to_mpf_string(16, 0, nearest), "-0.1a@2");
+    assert_eq!(Float::from(0.001).to_mpf_string(10, 3, nearest), "0.1e-2");
+    assert_eq!(Float::from(f64::consts::PI).to_mpf_string(10, 6, nearest), "0.314159e1");
+    assert_eq!(Float::from(f64::consts::PI).to_mpf_string(10, 0, nearest),
+               "0.31415926535897931e1");
+
+    assert_eq!(Float::zero(10).to_mpf_string(10, 0, nearest), "0.e0");
+    assert_eq!(Float::zero(10).to_mpf_string(16, 0, nearest), "0.@0");
+    assert_eq!(Float::neg_zero(10).to_mpf_string(10, 0, nearest), "-0.e0");
+    assert_eq!(Float::neg_infinity(10).to_mpf_string(10, 0, nearest), "-@Inf@");
+    assert_eq!(Float::nan(10).to_mpf_string(10, 0, nearest), "@NaN@");
+}
+
+#[test]
+fn mpf_input() {
+    for &(s, radix, x) in &[("0.1101@101", 2, 26.0), ("0.1101e5", -2, 26.0),
+                            ("0.1101e-1", 2, 0.40625), ("0.15e1", 10, 1.5), ("0.15e1", -10, 1.5),
+                            ("-0.1a@2", -16, -26.0), ("0.1@-a", 16, 1.0 / 17592186044416.0),
+                            ("0.@0", 16, 0.0), ("-0.e0", -10, -0.0), ("12.5", 8, 10.625),
+                            ("-@inf@", 10, -1.0 / 0.0)] {
+        let f = Float::from_mpf_str(s, radix, 53, RoundingMode::NearestEven).unwrap();
+        assert!(f == x && f.sign() == Float::from(x).sign(), "{} {}: {:?}", s, radix, f);
+    }
+    assert!(Float::from_mpf_str("@NaN@", -36, 53, RoundingMode::NearestEven).unwrap()
+            .sign().is_none());
+    for &(s, radix) in &[("", 10), ("0.1101e5", 2), ("0.1e", 10), (".", 10), ("inf", 10),
+                         ("nan", 10), ("0.1@1.0", -16)] {
+        assert!(Float::from_mpf_str(s, radix, 53, RoundingMode::NearestEven).is_err(),
+                "{} {}", s, radix);
+    }
+}
+
+#[test]
+fn mpf_round_trip() {
+    for x in values() {
+        for &radix in &[2, 3, 8, 10, 16, 36] {
+            let s = x.to_mpf_string(radix, 0, RoundingMode::NearestEven);
+            let y = Float::from_mpf_str(&s, -(radix as i32), x.precision(),
+                                        RoundingMode::NearestEven);
+            let y = y.unwrap();
+            assert!(same(&x, &y), "{:?} {}: {} {:?}", x, radix, s, y);
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn mpf_radix_too_small() {
+    let _ = Float::from_mpf_str("1", -1, 53, RoundingMode::NearestEven);
+}
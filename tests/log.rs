@@ -0,0 +1,198 @@
+extern crate float;
+
+mod common;
+
+use common::{same, at_precisions};
+use float::{Float, Exception, RoundingMode};
+
+use std::cmp::Ordering;
+
+const LN2: &'static str =
+    "0.6931471805599453094172321214581765680755001343602552541206800094933936219696947156058633269964186875";
+
+fn values() -> Vec<Float> {
+    at_precisions(&[2.0, 0.5, 3.0, 0.75, 1.25, 1.0009765625, 0.99, 1e-3, 1e-20, 1e20, 100.0,
+                    12345.678, 0.693147180559945],
+                  &[2, 10, 53, 100])
+}
+
+#[test]
+fn special() {
+    let nearest = RoundingMode::NearestEven;
+    assert!(Float::nan(10).ln().sign().is_none());
+    assert!(same(&Float::infinity(10).ln(), &Float::infinity(10)));
+    Float::clear_flags();
+    assert!(Float::neg_infinity(10).ln().sign().is_none());
+    assert!(Float::from(-1).ln().sign().is_none());
+    assert!(Float::flags().contains(Exception::Invalid));
+    Float::clear_flags();
+    assert!(same(&Float::zero(10).ln(), &Float::neg_infinity(10)));
+    assert!(same(&Float::neg_zero(10).ln(), &Float::neg_infinity(10)));
+    assert!(Float::flags().contains(Exception::DivideByZero));
+    let (one, ord) = Float::from(1).ln_ternary(nearest);
+    assert!(same(&one, &Float::zero(10)) && ord == Ordering::Equal);
+
+    assert!(Float::nan(10).ln_1p().sign().is_none());
+    assert!(same(&Float::infinity(10).ln_1p(), &Float::infinity(10)));
+    assert!(Float::neg_infinity(10).ln_1p().sign().is_none());
+    assert!(Float::from(-2).ln_1p().sign().is_none());
+    Float::clear_flags();
+    assert!(same(&Float::from(-1).ln_1p(), &Float::neg_infinity(10)));
+    assert!(Float::flags().contains(Exception::DivideByZero));
+    assert!(same(&Float::zero(10).ln_1p(), &Float::zero(10)));
+    assert!(same(&Float::neg_zero(10).ln_1p(), &Float::neg_zero(10)));
+
+    for &(_, f) in &[("log2", Float::log2 as fn(Float) -> Float), ("log10", Float::log10)] {
+        assert!(f(Float::nan(10)).sign().is_none());
+        assert!(same(&f(Float::infinity(10)), &Float::infinity(10)));
+        assert!(f(Float::neg_infinity(10)).sign().is_none());
+        assert!(f(Float::from(-1)).sign().is_none());
+        assert!(same(&f(Float::zero(10)), &Float::neg_infinity(10)));
+        assert!(same(&f(Float::from(1)), &Float::zero(10)));
+    }
+}
+
+#[test]
+fn ln2() {
+    for &prec in &[1, 2, 24, 53, 64, 200] {
+        let want = Float::from_str_with_precision(LN2, prec, RoundingMode::NearestEven).unwrap();
+        assert_eq!(Float::from(2).with_precision(prec).ln(), want);
+        assert_eq!(-Float::from(0.5).with_precision(prec).ln(), want);
+        assert_eq!(Float::from(1).with_precision(prec).ln_1p(), want);
+    }
+}
+
+#[test]
+fn correctly_rounded() {
+    let (down, up) = (RoundingMode::TowardNegative, RoundingMode::TowardPositive);
+    let mut xs = values();
+    // ln_1p also handles the negative values, including ones near -1.
+    xs.extend(values().into_iter().filter(|x| *x < 1).map(|x| -x));
+    for x in xs {
+        let prec = x.precision();
+        let mut fs = vec![("ln_1p", Float::ln_1p_ternary as fn(Float, RoundingMode) -> _)];
+        if x > 0 && x != 1 {
+            fs.push(("ln", Float::ln_ternary));
+            // exact powers are handled by `exact_powers`
+            let f: f64 = x.clone().into();
+            if f.log2().fract() != 0.0 {
+                fs.push(("log2", Float::log2_ternary));
+            }
+            if f.log10().fract() != 0.0 {
+                fs.push(("log10", Float::log10_ternary));
+            }
+        }
+        for &(ref name, f) in &fs {
+            let (lo, lo_ord) = f(x.clone(), down);
+            let (hi, hi_ord) = f(x.clone(), up);
+            assert!(lo_ord == Ordering::Less && hi_ord == Ordering::Greater, "{} {:?}", name, x);
+            assert_eq!(lo.clone().next_above(), hi);
+
+            // the nearest is whichever bound is closer to a more
+            // precise result.
+            let (nearest, _) = f(x.clone(), RoundingMode::NearestEven);
+            let precise = f(x.clone().with_precision(prec + 100), RoundingMode::NearestEven).0;
+            let mid = Float::add_to(&lo, &hi, prec + 1, RoundingMode::NearestEven).mul_exp2(-1);
+            let want = if precise < mid { lo } else { hi };
+            assert_eq!(nearest, want, "{} {:?}", name, x);
+        }
+    }
+}
+
+#[test]
+fn huge_and_tiny() {
+    // ln(2**k) == k ln(2), and multiplying by a power of two is exact
+    let one = Float::from(1).with_precision(53);
+    let ln2 = Float::from(2).with_precision(53).ln();
+    assert_eq!(one.clone().mul_exp2(1 << 40).ln(), ln2.clone().mul_exp2(40));
+    assert_eq!(one.clone().mul_exp2(1 << 62).ln(), ln2.clone().mul_exp2(62));
+    assert_eq!(one.mul_exp2(-(1 << 62)).ln(), -ln2.mul_exp2(62));
+    let max = Float::max(53).ln();
+    assert!(max > 0 && max < Float::from(1).mul_exp2(63));
+    let min = Float::min_positive(53).ln();
+    assert!(min < 0 && min > Float::from(-1).mul_exp2(63));
+
+    // these have a lowest bit far above 2**62, and aren't powers of ten
+    let log10_2 = Float::from(2).with_precision(53).log10();
+    let max = Float::max(53).log10();
+    assert!(max > 0 && max < Float::from(1).mul_exp2(62));
+    let one = Float::from(1).with_precision(53);
+    assert_eq!(one.mul_exp2(1 << 62).log10(), log10_2.mul_exp2(62));
+}
+
+#[test]
+fn ln_1p_tiny() {
+    let down = RoundingMode::TowardNegative;
+    for &prec in &[2, 10, 53, 100] {
+        for &e in &[-200, -1 << 40, -(1 << 62)] {
+            let x = Float::from(1).with_precision(prec).mul_exp2(e);
+            assert_eq!(x.clone().ln_1p(), x);
+            assert_eq!(x.clone().ln_1p_round(down), x.clone().next_below());
+            let y = -x.clone();
+            assert_eq!(y.clone().ln_1p(), y);
+            assert_eq!(y.clone().ln_1p_round(down), y.clone().next_below());
+        }
+        let x = Float::min_positive(prec);
+        assert_eq!(x.clone().ln_1p(), x);
+        assert_eq!((-x.clone()).ln_1p(), -x);
+    }
+}
+
+#[test]
+fn ln_1p_near_minus_one() {
+    // -1 + 2**-1000 is exact with 1000 bits
+    let tiny = Float::from(1).with_precision(53).mul_exp2(-1000);
+    let x = Float::sub_to(&tiny, &Float::from(1), 1000, RoundingMode::NearestEven);
+    assert_eq!(Float::ln_1p_to(&x, 53, RoundingMode::NearestEven), tiny.ln());
+}
+
+#[test]
+fn exact_powers() {
+    let nearest = RoundingMode::NearestEven;
+    let one = Float::from(1).with_precision(53);
+    for &n in &[-1074, -1, 0, 1, 1023, 1 << 62, -(1 << 62)] {
+        let (x, ord) = one.clone().mul_exp2(n).log2_ternary(nearest);
+        assert_eq!((x, ord), (Float::from(n), Ordering::Equal));
+    }
+    // the exponent is rounded like any other integer
+    let (x, ord) = one.clone().mul_exp2((1 << 62) + 1).log2_ternary(nearest);
+    assert_eq!((x, ord), (Float::from(1i64 << 62), Ordering::Less));
+
+    let mut power = 1.0;
+    for n in 0..23 {
+        let (x, ord) = Float::from(power).log10_ternary(nearest);
+        assert_eq!((x, ord), (Float::from(n), Ordering::Equal));
+        power *= 10.0;
+    }
+    let big = Float::from_str_with_precision("1e100", 233, nearest).unwrap();
+    assert_eq!(big.log10_ternary(nearest), (Float::from(100), Ordering::Equal));
+    // 1e23 and 0.1 aren't exact in binary, so are only near powers of ten
+    let (x, ord) = Float::from(1e23).log10_ternary(nearest);
+    assert!(x == 23 && ord != Ordering::Equal);
+    let (x, ord) = Float::from(0.1).log10_ternary(nearest);
+    assert!(x == -1 && ord != Ordering::Equal);
+}
+
+#[test]
+fn decibels() {
+    // 20 log10(2) dB, and back again
+    let db = Float::from(2).with_precision(53).log10() * Float::from(20);
+    assert_eq!(db, Float::from(6.020599913279624));
+    let gain = (db / Float::from(20)).exp10();
+    assert_eq!(gain, Float::from(2));
+    // an octave above 440 Hz
+    let octaves = Float::from(880).with_precision(53).log2() - Float::from(440).with_precision(53).log2();
+    assert_eq!(octaves, 1);
+}
+
+#[test]
+fn narrow_exp_range() {
+    // splitting off the exponent is exact, even if the parts are
+    // outside the exponent range.
+    let one = Float::from(1).with_precision(61);
+    let x = one.clone().mul_exp2(-60) + one;
+    Float::set_exp_range(1, 100);
+    let (y, ord) = x.ln_ternary(RoundingMode::TowardPositive);
+    Float::reset_exp_range();
+    assert!(y > 0 && ord == Ordering::Greater);
+}
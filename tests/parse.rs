@@ -0,0 +1,311 @@
+extern crate float;
+extern crate rand;
+
+use float::{Float, RoundingMode, Exception};
+use rand::Rng;
+
+use std::{iter, mem};
+
+fn parse(s: &str, prec: u32, mode: RoundingMode) -> Float {
+    Float::from_str_with_precision(s, prec, mode).unwrap()
+}
+
+// strings representing random values spread across the range of
+// f64, with varying numbers of digits.
+fn strings() -> Vec<String> {
+    let mut rng = rand::thread_rng();
+    let mut v: Vec<String> = ["0", "-0", "1", "0.1", ".5", "5.", "-2.5e-3", "1E10",
+                              "123456789012345678901234567890", "1e+308", "1e-300",
+                              "1.7976931348623157e308", "1.7976931348623158e308",
+                              "2.2250738585072014e-308", "9007199254740993",
+                              "0.000000000000000000000000000000000000001",
+                              "00000000000000000000000000012.5000000000000000000000"]
+        .iter().map(|s| s.to_string()).collect();
+    while v.len() < 3000 {
+        let x: f64 = unsafe { mem::transmute(rng.gen::<u64>()) };
+        if x.is_finite() && x.abs() >= 2.2250738585072014e-308 {
+            let digits = rng.gen_range(0, 40);
+            v.push(format!("{:.*e}", digits, x));
+            v.push(format!("{}", x));
+        }
+    }
+    v
+}
+
+// `x` formatted with enough digits to distinguish it from values
+// very close to it.
+fn long_decimal(x: Float) -> String {
+    format!("{:.800e}", x)
+}
+
+#[test]
+fn matches_f64() {
+    for s in strings() {
+        let x: f64 = s.parse().unwrap();
+        let f: Float = s.parse().unwrap();
+        assert!(f == x && f.sign() == Float::from(x).sign(), "{}: {:?} != {}", s, f, x);
+        assert_eq!(f.precision(), 53);
+    }
+}
+
+#[test]
+fn halfway() {
+    let mut rng = rand::thread_rng();
+    for _ in 0..200 {
+        let x = rng.gen_range(1.0, 2.0) * 2f64.powi(rng.gen_range(-1000, 1000));
+        // the exact midpoint between `x` and the next f64 above it,
+        // and values very slightly either side of it.
+        let mid = Float::from(x).with_precision(54).next_above();
+        let near = mid.clone().with_precision(2000);
+        let strings = [long_decimal(mid),
+                       long_decimal(near.clone().next_above()),
+                       long_decimal(near.next_below())];
+
+        for s in &strings {
+            let f = parse(s, 53, RoundingMode::NearestEven);
+            let y: f64 = s.parse().unwrap();
+            assert!(f == y, "{}: {:?} != {}", s, f, y);
+        }
+    }
+}
+
+#[test]
+fn directed() {
+    for s in strings() {
+        let lo = parse(&s, 53, RoundingMode::TowardNegative);
+        let hi = parse(&s, 53, RoundingMode::TowardPositive);
+        let zero = parse(&s, 53, RoundingMode::TowardZero);
+        let near = parse(&s, 53, RoundingMode::NearestEven);
+        assert!(lo == hi || lo.clone().next_above() == hi, "{}: {:?} {:?}", s, lo, hi);
+        assert!(near == lo || near == hi, "{}", s);
+        if s.starts_with('-') {
+            assert!(zero == hi, "{}", s);
+        } else {
+            assert!(zero == lo, "{}", s);
+        }
+    }
+    let exact = parse("0.375", 2, RoundingMode::TowardZero);
+    assert_eq!(exact, 0.375);
+    assert_eq!(parse("0.1", 4, RoundingMode::TowardNegative), 0.09375);
+    assert_eq!(parse("0.1", 4, RoundingMode::TowardPositive), 0.1015625);
+}
+
+#[test]
+fn round_trip() {
+    let mut rng = rand::thread_rng();
+    for &prec in &[1, 2, 10, 24, 53, 64, 113, 300] {
+        for _ in 0..300 {
+            let x: f64 = unsafe { mem::transmute(rng.gen::<u64>()) };
+            if !x.is_finite() {
+                continue
+            }
+            let f = Float::from(x).with_precision(prec);
+            let s = format!("{}", f);
+            assert_eq!(parse(&s, prec, RoundingMode::NearestEven), f);
+
+            let g = f.mul_exp2(rng.gen_range(-1 << 40, 1 << 40));
+            for s in &[format!("{:e}", g), format!("{:E}", g)] {
+                assert_eq!(parse(s, prec, RoundingMode::NearestEven), g);
+            }
+        }
+    }
+}
+
+#[test]
+fn huge_exponents() {
+    let s = "8.05723224506582382563e330985980541";
+    let f = parse(s, 64, RoundingMode::NearestEven);
+    assert_eq!(format!("{:e}", f), s);
+    let s = "-3.48961867139819334916e-338929644074912";
+    let f = parse(s, 64, RoundingMode::NearestEven);
+    assert_eq!(format!("{:e}", f), s);
+
+    Float::clear_flags();
+    let inf = parse("1e99999999999999999999999", 53, RoundingMode::NearestEven);
+    assert_eq!(inf, Float::infinity(53));
+    assert!(Float::flags().contains(Exception::Overflow));
+    let max = parse("1e99999999999999999999999", 53, RoundingMode::TowardZero);
+    assert_eq!(max, Float::max(53));
+
+    Float::clear_flags();
+    let zero = parse("-1e-99999999999999999999999", 53, RoundingMode::NearestEven);
+    assert_eq!(zero.sign(), Some(float::Sign::Neg));
+    assert_eq!(zero, 0.0);
+    assert!(Float::flags().contains(Exception::Underflow));
+}
+
+#[test]
+fn subnormal() {
+    Float::set_exp_range(-1022, 1023);
+    let mut rng = rand::thread_rng();
+    let mut strings = vec!["5e-324".to_string(), "2.4703282292062327e-324".to_string(),
+                           "2.4703282292062328e-324".to_string(), "1e-330".to_string(),
+                           "2.2250738585072011e-308".to_string()];
+    for _ in 0..1000 {
+        let bits = rng.gen::<u64>() & ((1 << 52) - 1);
+        let x: f64 = unsafe { mem::transmute(bits) };
+        strings.push(format!("{:.*e}", rng.gen_range(0, 25), x));
+    }
+    for s in &strings {
+        let f = parse(s, 53, RoundingMode::NearestEven);
+        let x: f64 = s.parse().unwrap();
+        assert!(f == x, "{}: {:?} != {}", s, f, x);
+    }
+    Float::reset_exp_range();
+}
+
+#[test]
+fn long_inputs() {
+    // 1 + 2**-53 is halfway between two f64s, and anything past it,
+    // however far out, should round up.
+    let mid = format!("{:.60}", Float::from(1).with_precision(54).next_above());
+    let mut s = mid.trim_right_matches('0').to_string();
+    assert_eq!(parse(&s, 53, RoundingMode::NearestEven), 1.0);
+    s.extend(iter::repeat('0').take(10000));
+    s.push('1');
+    assert_eq!(parse(&s, 53, RoundingMode::NearestEven), 1.0 + 2.0 * 2f64.powi(-53));
+
+    let ones: String = iter::repeat('1').take(300).collect();
+    let x: f64 = ones.parse().unwrap();
+    assert_eq!(parse(&ones, 53, RoundingMode::NearestEven), x);
+}
+
+#[test]
+fn hex() {
+    let pi = std::f64::consts::PI;
+    for &(s, x) in &[("0x1.921fb54442d18p+1", pi), ("0X1.921FB54442D18P+1", pi),
+                     ("-0x1p-1074", -5e-324), ("0x1.8", 1.5), ("0x.8p1", 1.0),
+                     ("0x10", 16.0), ("+0x1e", 30.0), ("0x0p0", 0.0), ("0x3p-1", 1.5),
+                     ("0x1.fffffffffffff7p1023", 1.7976931348623157e308)] {
+        let f: Float = s.parse().unwrap();
+        assert!(f == x, "{}: {:?} != {}", s, f, x);
+    }
+    assert_eq!(parse("0x1.ffp0", 4, RoundingMode::TowardZero), 1.875);
+    assert_eq!(parse("0x1.ffp0", 4, RoundingMode::TowardPositive), 2.0);
+    let huge = parse("0x1p99999999999999999999999", 53, RoundingMode::TowardZero);
+    assert_eq!(huge, Float::max(53));
+    assert_eq!(parse("-0x1p-99999999999999999999999", 53, RoundingMode::NearestEven).sign(),
+               Some(float::Sign::Neg));
+}
+
+#[test]
+fn hex_round_trip() {
+    let mut rng = rand::thread_rng();
+    for &prec in &[1, 2, 3, 4, 5, 53, 64, 113, 300] {
+        for _ in 0..300 {
+            let x: f64 = unsafe { mem::transmute(rng.gen::<u64>()) };
+            if !x.is_finite() {
+                continue
+            }
+            let f = Float::from(x).with_precision(prec)
+                .mul_exp2(rng.gen_range(-1 << 50, 1 << 50));
+            for s in &[format!("{:x}", f), format!("{:X}", f)] {
+                let g = parse(s, prec, RoundingMode::TowardZero);
+                assert!(g == f && g.sign() == f.sign(), "{}: {:?} != {:?}", s, g, f);
+            }
+        }
+    }
+}
+
+#[test]
+fn radix() {
+    for &(s, radix, x) in &[("ff.8", 16, 255.5), ("FF.8@-1", 16, 255.5 / 16.0),
+                            ("-1.1e-1", 2, -0.75), ("1.1@-1", 2, 0.75), ("z@1", 36, 1260.0),
+                            ("Z", 36, 35.0), ("inf", 36, 24171.0), ("1e5", 16, 485.0),
+                            ("1e5", 8, 32768.0), (".1", 3, 1.0 / 3.0), ("-0.0", 5, -0.0),
+                            ("inf", 16, 1.0 / 0.0), ("-@Inf@", 36, -1.0 / 0.0)] {
+        let f = Float::from_str_radix(s, radix, 53, RoundingMode::NearestEven).unwrap();
+        assert!(f == x && f.sign() == Float::from(x).sign(), "{} {}: {:?}", s, radix, f);
+    }
+    for &(s, radix) in &[("nan", 10), ("@nan@", 36), ("NaN", 16)] {
+        let f = Float::from_str_radix(s, radix, 53, RoundingMode::NearestEven).unwrap();
+        assert!(f.sign().is_none());
+    }
+    for &(s, radix) in &[("2", 2), ("nan", 23), ("1@", 16), ("1e5", 11), ("a", 10), ("", 7),
+                         ("@inf", 36), ("0x1", 16), ("1@1.5", 20)] {
+        assert!(Float::from_str_radix(s, radix, 53, RoundingMode::NearestEven).is_err(),
+                "{} {}", s, radix);
+    }
+
+    // one third in base 3 has an exponent far beyond the range of
+    // `f64`
+    let third = Float::from_str_radix("1@-1000000000", 3, 53, RoundingMode::NearestEven);
+    let third = third.unwrap();
+    assert_eq!(format!("{:.10e}", third), "1.9069423452e-477121255");
+}
+
+#[test]
+#[should_panic]
+fn radix_too_large() {
+    let _ = Float::from_str_radix("1", 37, 53, RoundingMode::NearestEven);
+}
+
+#[test]
+fn debug() {
+    let mut rng = rand::thread_rng();
+    let mut values = vec![Float::zero(3), Float::neg_zero(1), Float::infinity(100),
+                          Float::neg_infinity(2), Float::nan(53), Float::max(10).mul_exp2(-1),
+                          Float::min_positive(64)];
+    for &prec in &[1, 2, 53, 64, 200] {
+        for _ in 0..100 {
+            let x: f64 = unsafe { mem::transmute(rng.gen::<u64>()) };
+            values.push(Float::from(x).with_precision(prec)
+                        .mul_exp2(rng.gen_range(-1 << 62, 1 << 62)));
+        }
+    }
+    for f in values {
+        let s = format!("{:?}", f);
+        let g = Float::from_debug_str(&s).unwrap();
+        assert_eq!(g.precision(), f.precision());
+        assert_eq!(g.sign(), f.sign());
+        assert!(g == f || f.sign().is_none(), "{}", s);
+        assert_eq!(format!("{:?}", g), s);
+    }
+
+    assert_eq!(format!("{:?}", Float::from(-1.5).with_precision(4)), "-1100 * 2^(0 - 4)");
+    assert_eq!(Float::from_debug_str("+101 * 2^(-3 - 3)").unwrap(), 0.15625);
+    assert_eq!(format!("{:?}", Float::neg_zero(7)), "-0.0 (prec 7)");
+    assert_eq!(format!("{:?}", Float::nan(7)), "NaN (prec 7)");
+    // the sign of a NaN isn't visible through `sign`, but is kept
+    let neg_nan = -Float::nan(7);
+    assert_eq!(format!("{:?}", neg_nan), "-NaN (prec 7)");
+    assert_eq!(format!("{:?}", Float::from_debug_str("-NaN (prec 7)").unwrap()),
+               "-NaN (prec 7)");
+
+    for s in &["", "NaN", "+0.0", "+0.0 (prec 0)", "0.0 (prec 3)", "+inf (prec +3)",
+               "nan (prec 3)", "101 * 2^(0 - 3)", "+101 * 2^(0 - 4)", "+011 * 2^(0 - 3)",
+               "+1.1 * 2^(0 - 3)", "+121 * 2^(0 - 3)", "+101 * 2^(0 - 3", "+101 * 2^(+1 - 3)",
+               "+101 * 2^(9223372036854775807 - 3)", "+101 * 2^(0 - -3)", "+101 *2^(0 - 3)",
+               "+101 * 2^(0-3)", " +101 * 2^(0 - 3)", "+NaN (prec 3)"] {
+        assert!(Float::from_debug_str(s).is_err(), "{}", s);
+    }
+}
+
+#[test]
+fn special() {
+    for s in &["inf", "+inf", "Infinity", "INF", "iNfInItY"] {
+        assert_eq!(s.parse::<Float>().unwrap(), Float::infinity(53));
+    }
+    for s in &["-inf", "-Infinity"] {
+        assert_eq!(s.parse::<Float>().unwrap(), Float::neg_infinity(53));
+    }
+    for s in &["nan", "NaN", "-nan", "+NAN"] {
+        assert!(s.parse::<Float>().unwrap().sign().is_none());
+    }
+    let z = parse("-0.000", 10, RoundingMode::NearestEven);
+    assert_eq!(z, 0.0);
+    assert_eq!(z.sign(), Some(float::Sign::Neg));
+    assert_eq!(z.precision(), 10);
+}
+
+#[test]
+fn errors() {
+    let empty = "".parse::<Float>().unwrap_err();
+    assert_eq!(empty.to_string(), "cannot parse float from empty string");
+    for s in &["-", "+", ".", "e5", "1e", "1e+", "1.2.3", "0x", "0xp1", "0x1p", "0x1.2.3", "0x1g", "1p3", " 1", "1 ", "--1",
+               "1e5.0", "infinite", "nanx", "1_000", "١"] {
+        let err = s.parse::<Float>().unwrap_err();
+        assert_eq!(err.to_string(), "invalid float literal");
+        assert!(err != empty);
+    }
+}
@@ -0,0 +1,190 @@
+extern crate float;
+
+mod common;
+
+use common::same;
+use float::{Float, Exception, RoundingMode};
+
+use std::cmp::Ordering;
+
+fn f(x: f64) -> Float {
+    Float::from(x).with_precision(53)
+}
+
+#[test]
+fn special() {
+    let nan = Float::nan(53);
+    let (inf, neg_inf) = (Float::infinity(53), Float::neg_infinity(53));
+    let (zero, neg_zero) = (Float::zero(53), Float::neg_zero(53));
+    let one = f(1.0);
+
+    // x**0 == 1**y == 1 for any x and y, even NaN
+    for x in &[nan.clone(), inf.clone(), neg_inf.clone(), zero.clone(), f(-3.5)] {
+        assert!(same(&x.clone().pow(&zero), &one), "{:?}", x);
+        assert!(same(&x.clone().pow(&neg_zero), &one), "{:?}", x);
+        assert!(same(&one.clone().pow(x), &one), "{:?}", x);
+    }
+    assert!(nan.clone().pow(&f(2.0)).sign().is_none());
+    assert!(f(2.0).pow(&nan).sign().is_none());
+    assert!(f(-1.0).pow(&nan).sign().is_none());
+
+    // infinite exponents
+    assert!(same(&f(-1.0).pow(&inf), &one));
+    assert!(same(&f(-1.0).pow(&neg_inf), &one));
+    for &(x, pos, neg) in &[(0.5, &zero, &inf), (-0.5, &zero, &inf), (0.0, &zero, &inf),
+                            (2.0, &inf, &zero), (-2.0, &inf, &zero)] {
+        assert!(same(&f(x).pow(&inf), pos), "{}", x);
+        assert!(same(&f(x).pow(&neg_inf), neg), "{}", x);
+    }
+    assert!(same(&neg_inf.clone().pow(&inf), &inf));
+    assert!(same(&neg_inf.clone().pow(&neg_inf), &zero));
+
+    // signed zeros, with the sign kept for odd integer exponents
+    Float::clear_flags();
+    assert!(same(&zero.clone().pow(&f(3.0)), &zero));
+    assert!(same(&neg_zero.clone().pow(&f(3.0)), &neg_zero));
+    assert!(same(&neg_zero.clone().pow(&f(2.0)), &zero));
+    assert!(same(&neg_zero.clone().pow(&f(0.5)), &zero));
+    assert!(!Float::flags().contains(Exception::DivideByZero));
+    assert!(same(&zero.clone().pow(&f(-3.0)), &inf));
+    assert!(same(&neg_zero.clone().pow(&f(-3.0)), &neg_inf));
+    assert!(same(&neg_zero.clone().pow(&f(-2.0)), &inf));
+    assert!(same(&neg_zero.clone().pow(&f(-0.5)), &inf));
+    assert!(Float::flags().contains(Exception::DivideByZero));
+
+    // infinite bases
+    assert!(same(&inf.clone().pow(&f(0.5)), &inf));
+    assert!(same(&inf.clone().pow(&f(-3.0)), &zero));
+    assert!(same(&neg_inf.clone().pow(&f(3.0)), &neg_inf));
+    assert!(same(&neg_inf.clone().pow(&f(2.0)), &inf));
+    assert!(same(&neg_inf.clone().pow(&f(-3.0)), &neg_zero));
+    assert!(same(&neg_inf.clone().pow(&f(-0.5)), &zero));
+
+    // negative bases need integer exponents
+    Float::clear_flags();
+    assert!(f(-2.0).pow(&f(0.5)).sign().is_none());
+    assert!(Float::flags().contains(Exception::Invalid));
+    assert!(same(&f(-1.0).pow(&f(3.0)), &-one.clone()));
+    assert!(same(&f(-1.0).pow(&Float::from(1).mul_exp2(100)), &one));
+    assert_eq!(f(-2.0).pow(&f(-3.0)), -0.125);
+}
+
+#[test]
+fn exact_powers() {
+    let nearest = RoundingMode::NearestEven;
+    Float::clear_flags();
+    for &(x, y, want) in &[(3.0, 2.0, 9.0), (-3.0, 3.0, -27.0), (-3.0, 4.0, 81.0),
+                           (4.0, 0.5, 2.0), (9.0, 1.5, 27.0), (0.25, -0.5, 2.0),
+                           (2.0, -1074.0, 5e-324), (6561.0, 0.125, 3.0),
+                           (0.75, 3.0, 0.421875), (0.0625, -0.75, 8.0)] {
+        let (z, ord) = f(x).pow_ternary(&f(y), nearest);
+        assert_eq!((z, ord), (f(want), Ordering::Equal), "{} {}", x, y);
+    }
+    assert!(!Float::flags().contains(Exception::Inexact));
+    // but 2**0.25 and 6**0.5 are irrational, and 3**-1 isn't a
+    // binary fraction
+    for &(x, y, want) in &[(2.0, 0.25, 1.189207115002721), (6.0, 0.5, 2.449489742783178),
+                           (3.0, -1.0, 1.0 / 3.0)] {
+        let (z, ord) = f(x).pow_ternary(&f(y), nearest);
+        assert!(z == want && ord != Ordering::Equal, "{} {}", x, y);
+    }
+
+    // 9 needs four bits, and is halfway between 8 and 10
+    let three = Float::from(3).with_precision(3);
+    assert_eq!(three.clone().pow_ternary(&Float::from(2), nearest),
+               (Float::from(8), Ordering::Less));
+    assert_eq!(three.pow_ternary(&Float::from(2), RoundingMode::TowardPositive),
+               (Float::from(10), Ordering::Greater));
+
+    let big = Float::from_str_with_precision("1e100", 233, nearest).unwrap();
+    let ten = Float::from(10).with_precision(233);
+    assert_eq!(ten.pow_ternary(&Float::from(100), nearest), (big, Ordering::Equal));
+}
+
+#[test]
+fn consistency() {
+    let nearest = RoundingMode::NearestEven;
+    let half = f(0.5);
+    for &x in &[2.0, 3.0, 0.1, 1e300, 12345.678] {
+        assert_eq!(f(x).pow(&half), f(x).sqrt());
+        assert_eq!(f(x).pow(&f(-1.0)), f(1.0) / f(x));
+        assert_eq!(f(x).pow(&f(2.0)), f(x) * f(x));
+    }
+    for &y in &[0.5, -7.25, 1e-3, 100.0, 12345.678, -1074.5] {
+        for &prec in &[10, 53, 100] {
+            let y = Float::from(y).with_precision(prec);
+            let two = Float::from(2).with_precision(prec);
+            let ten = Float::from(10).with_precision(prec);
+            assert_eq!(two.pow_ternary(&y, nearest), y.clone().exp2_ternary(nearest));
+            assert_eq!(ten.pow_ternary(&y, nearest), y.clone().exp10_ternary(nearest));
+        }
+    }
+}
+
+#[test]
+fn correctly_rounded() {
+    let (down, up) = (RoundingMode::TowardNegative, RoundingMode::TowardPositive);
+    for &x in &[3.0, 0.75, 1.0009765625, 0.99, 1e-20, 1e20, 12345.678, -3.0, -0.99] {
+        for &y in &[0.5f64, -0.5, 1.0 / 3.0, 7.0, -7.0, 2.5, 100.0, -12.345, 1e-10] {
+            if x < 0.0 && y.fract() != 0.0 {
+                continue
+            }
+            for &prec in &[2, 10, 53, 100] {
+                let x = Float::from(x).with_precision(prec);
+                let y = Float::from(y).with_precision(prec);
+                let (lo, lo_ord) = x.clone().pow_ternary(&y, down);
+                let (hi, hi_ord) = x.clone().pow_ternary(&y, up);
+                // small integer powers may well be exact
+                if lo_ord == Ordering::Equal {
+                    assert!(hi == lo && hi_ord == Ordering::Equal, "{:?} {:?}", x, y);
+                    continue
+                }
+                assert!(lo_ord == Ordering::Less && hi_ord == Ordering::Greater,
+                        "{:?} {:?}", x, y);
+                assert_eq!(lo.clone().next_above(), hi);
+
+                // the nearest is whichever bound is closer to a more
+                // precise result.
+                let nearest = x.clone().pow(&y);
+                let precise = Float::pow_to(&x, &y, prec + 100, RoundingMode::NearestEven);
+                let mid = Float::add_to(&lo, &hi, prec + 1, RoundingMode::NearestEven).mul_exp2(-1);
+                let want = if precise < mid { lo } else { hi };
+                assert_eq!(nearest, want, "{:?} {:?}", x, y);
+            }
+        }
+    }
+}
+
+#[test]
+fn out_of_range() {
+    let huge = Float::from(1).mul_exp2(63);
+    Float::clear_flags();
+    assert_eq!(f(2.0).pow(&huge), Float::infinity(53));
+    assert_eq!(f(3.0).pow(&huge), Float::infinity(53));
+    assert!(same(&f(-3.0).pow(&Float::from(1).mul_exp2(70)), &Float::infinity(53)));
+    assert!(Float::flags().contains(Exception::Overflow));
+    Float::clear_flags();
+    assert!(same(&f(0.5).pow(&huge), &Float::zero(53)));
+    assert!(same(&f(3.0).pow(&-huge.clone()), &Float::zero(53)));
+    assert!(Float::flags().contains(Exception::Underflow));
+
+    // (1 + 2**-100)**(2**64) == e**(2**-36), near enough
+    let one = Float::from(1).with_precision(101);
+    let x = one.clone().mul_exp2(-100) + one;
+    let z = x.pow(&Float::from(1).mul_exp2(64));
+    assert!(z > 1 && z < 1.0 + 1.0 / (1u64 << 35) as f64);
+    // a tiny power rounds like anything near 1
+    let z = f(3.0).pow_round(&Float::from(1).mul_exp2(-200), RoundingMode::TowardPositive);
+    assert_eq!(z, f(1.0).next_above());
+
+    Float::set_exp_range(-1022, 1023);
+    Float::clear_flags();
+    let max = f(2.0).pow(&f(1023.0));
+    assert!(!Float::flags().contains(Exception::Overflow));
+    assert_eq!(f(2.0).pow(&f(1024.0)), Float::infinity(53));
+    assert!(Float::flags().contains(Exception::Overflow));
+    let tiny = f(10.0).pow(&f(-320.0));
+    Float::reset_exp_range();
+    assert_eq!(max, Float::from(1).mul_exp2(1023));
+    assert_eq!(tiny, Float::from(1e-320));
+}
@@ -7,9 +7,11 @@
 extern crate quickcheck;
 extern crate float;
 
-use float::Float;
+use float::{Float, Sign, RoundingMode};
 use quickcheck::TestResult;
 
+use std::cmp::Ordering;
+
 fn ensure_ulp(a: Float, b: Float, count: f64) -> TestResult {
     let computed_p = a.precision();
     let p = b.precision();
@@ -76,6 +78,125 @@ fn bin_ulp<D, F>(x: Vec<f64>, y: Vec<f64>, prec: u32, ulp: f64,
     ensure_ulp(computed, exact, ulp)
 }
 
+// rounding down and up should bracket rounding to nearest, and be at
+// most an ulp apart, with rounding toward zero being one of them.
+fn directed<F>(x: Vec<f64>, y: Vec<f64>, prec: u32, mut op: F) -> TestResult
+    where F: FnMut(Float, &Float, RoundingMode) -> Float
+{
+    let x = mul_to_float(&x, prec);
+    let y = mul_to_float(&y, prec);
+
+    let down = op(x.clone(), &y, RoundingMode::TowardNegative);
+    let up = op(x.clone(), &y, RoundingMode::TowardPositive);
+    let near = op(x.clone(), &y, RoundingMode::NearestEven);
+    let zero = op(x, &y, RoundingMode::TowardZero);
+    if near.sign().is_none() {
+        return TestResult::discard();
+    }
+
+    assert!(down <= near && near <= up,
+            "{:?} <= {:?} <= {:?}", down, near, up);
+    assert!(down == up || down.clone().next_above() == up,
+            "{:?} and {:?} are not adjacent", down, up);
+    let toward_zero = if near.sign() == Some(Sign::Neg) { &up } else { &down };
+    assert!(zero == *toward_zero,
+            "{:?} != {:?}", zero, toward_zero);
+    TestResult::from_bool(true)
+}
+
+const MODES: [RoundingMode; 5] = [RoundingMode::NearestEven, RoundingMode::NearestAway,
+                                  RoundingMode::TowardZero,
+                                  RoundingMode::TowardPositive, RoundingMode::TowardNegative];
+
+// the reported rounding direction should match the result of
+// comparing against the same computation performed in double the
+// precision (falling back to its rounding direction if they're
+// equal).
+fn ternary<F>(x: Vec<f64>, y: Vec<f64>, prec: u32, mut op: F) -> TestResult
+    where F: FnMut(Float, &Float, RoundingMode) -> (Float, Ordering)
+{
+    let x = mul_to_float(&x, prec);
+    let y = mul_to_float(&y, prec);
+
+    for &mode in &MODES {
+        let (computed, ord) = op(x.clone(), &y, mode);
+        let (exact, exact_ord) = op(x.clone().with_precision(prec * 2),
+                                    &y.clone().with_precision(prec * 2),
+                                    mode);
+        if exact.sign().is_none() {
+            return TestResult::discard();
+        }
+        let expected = match computed.clone().with_precision(prec * 2).partial_cmp(&exact) {
+            Some(Ordering::Equal) => exact_ord,
+            o => o.unwrap(),
+        };
+        assert!(ord == expected,
+                "{:?}: {:?} ({:?}) vs. {:?}", mode, computed, ord, exact);
+    }
+    TestResult::from_bool(true)
+}
+
+// operating on values of different precisions should give the same
+// result as first (exactly) converting both to the larger precision.
+fn mixed<F>(x: Vec<f64>, y: Vec<f64>, x_prec: u32, y_prec: u32, mut op: F) -> TestResult
+    where F: FnMut(Float, &Float, RoundingMode) -> (Float, Ordering)
+{
+    let x = mul_to_float(&x, x_prec);
+    let y = mul_to_float(&y, y_prec);
+    let prec = std::cmp::max(x_prec, y_prec);
+
+    for &mode in &MODES {
+        let (computed, ord) = op(x.clone(), &y, mode);
+        let (expected, expected_ord) = op(x.clone().with_precision(prec),
+                                          &y.clone().with_precision(prec),
+                                          mode);
+        assert_eq!(computed.precision(), prec);
+        assert_eq!(computed.sign(), expected.sign());
+        if computed.sign().is_some() {
+            assert!(computed == expected,
+                    "{:?}: {:?} vs. {:?}", mode, computed, expected);
+        }
+        assert_eq!(ord, expected_ord);
+    }
+    assert_eq!(x.partial_cmp(&y),
+               x.clone().with_precision(prec).partial_cmp(&y.clone().with_precision(prec)));
+    TestResult::from_bool(true)
+}
+
+// rounding directly to `out_prec` should give the same answer as
+// rounding the exact result, which is approximated by computing in a
+// much higher precision (discarding the cases where that could round
+// differently).
+fn destination<F, G>(x: Vec<f64>, y: Vec<f64>, prec: u32, out_prec: u32,
+                     mut op_to: F, mut op: G) -> TestResult
+    where F: FnMut(&Float, &Float, u32, RoundingMode) -> Float,
+          G: FnMut(Float, &Float, RoundingMode) -> (Float, Ordering)
+{
+    let x = mul_to_float(&x, prec);
+    let y = mul_to_float(&y, prec);
+    let high = 4 * (prec + out_prec);
+
+    for &mode in &MODES {
+        let computed = op_to(&x, &y, out_prec, mode);
+        let (exact, ord) = op(x.clone().with_precision(high), &y, mode);
+        // an inexact result that lies on a rounding boundary might
+        // not round the same way as the exact one.
+        let (_, boundary) = exact.clone()
+            .with_precision_ternary(out_prec + 1, RoundingMode::TowardZero);
+        if ord != Ordering::Equal && boundary == Ordering::Equal {
+            return TestResult::discard()
+        }
+        let exact = exact.with_precision_round(out_prec, mode);
+        assert_eq!(computed.precision(), out_prec);
+        assert_eq!(computed.sign(), exact.sign());
+        if computed.sign().is_some() {
+            assert!(computed == exact,
+                    "{:?}: {:?} vs. {:?}", mode, computed, exact);
+        }
+    }
+    TestResult::from_bool(true)
+}
+
 const PREC_OFFSET: u32 = 100;
 
 #[quickcheck]
@@ -166,3 +287,131 @@ fn mul_div(x: Vec<f64>, y: Vec<f64>, prec: u16) -> TestResult {
             |x, y| (x * y.clone()) / y,
             Some(&|x, _| x.clone()))
 }
+
+#[quickcheck]
+fn add_directed(x: Vec<f64>, y: Vec<f64>, prec: u16) -> TestResult {
+    let prec = PREC_OFFSET + prec as u32;
+    directed(x, y, prec, |x, y, mode| x.add_round(y, mode))
+}
+#[quickcheck]
+fn sub_directed(x: Vec<f64>, y: Vec<f64>, prec: u16) -> TestResult {
+    let prec = PREC_OFFSET + prec as u32;
+    directed(x, y, prec, |x, y, mode| x.sub_round(y, mode))
+}
+#[quickcheck]
+fn mul_directed(x: Vec<f64>, y: Vec<f64>, prec: u16) -> TestResult {
+    let prec = PREC_OFFSET + prec as u32;
+    directed(x, y, prec, |x, y, mode| x.mul_round(y, mode))
+}
+#[quickcheck]
+fn div_directed(x: Vec<f64>, y: Vec<f64>, prec: u16) -> TestResult {
+    let prec = PREC_OFFSET + prec as u32;
+    directed(x, y, prec, |x, y, mode| x.div_round(y, mode))
+}
+#[quickcheck]
+fn sqrt_directed(x: Vec<f64>, prec: u16) -> TestResult {
+    let prec = PREC_OFFSET + prec as u32;
+    directed(x, vec![], prec, |x, _, mode| x.abs().sqrt_round(mode))
+}
+#[quickcheck]
+fn with_precision_directed(x: Vec<f64>, prec: u16) -> TestResult {
+    let prec = PREC_OFFSET + prec as u32;
+    directed(x, vec![], prec * 2, |x, _, mode| x.with_precision_round(prec, mode))
+}
+
+#[quickcheck]
+fn add_ternary(x: Vec<f64>, y: Vec<f64>, prec: u16) -> TestResult {
+    let prec = PREC_OFFSET + prec as u32;
+    ternary(x, y, prec, |x, y, mode| x.add_ternary(y, mode))
+}
+#[quickcheck]
+fn sub_ternary(x: Vec<f64>, y: Vec<f64>, prec: u16) -> TestResult {
+    let prec = PREC_OFFSET + prec as u32;
+    ternary(x, y, prec, |x, y, mode| x.sub_ternary(y, mode))
+}
+#[quickcheck]
+fn mul_ternary(x: Vec<f64>, y: Vec<f64>, prec: u16) -> TestResult {
+    let prec = PREC_OFFSET + prec as u32;
+    ternary(x, y, prec, |x, y, mode| x.mul_ternary(y, mode))
+}
+#[quickcheck]
+fn div_ternary(x: Vec<f64>, y: Vec<f64>, prec: u16) -> TestResult {
+    let prec = PREC_OFFSET + prec as u32;
+    ternary(x, y, prec, |x, y, mode| x.div_ternary(y, mode))
+}
+#[quickcheck]
+fn sqrt_ternary(x: Vec<f64>, prec: u16) -> TestResult {
+    let prec = PREC_OFFSET + prec as u32;
+    ternary(x, vec![], prec, |x, _, mode| x.abs().sqrt_ternary(mode))
+}
+
+#[quickcheck]
+fn add_mixed(x: Vec<f64>, y: Vec<f64>, x_prec: u16, y_prec: u16) -> TestResult {
+    let (x_prec, y_prec) = (PREC_OFFSET + x_prec as u32, PREC_OFFSET + y_prec as u32);
+    mixed(x, y, x_prec, y_prec, |x, y, mode| x.add_ternary(y, mode))
+}
+#[quickcheck]
+fn sub_mixed(x: Vec<f64>, y: Vec<f64>, x_prec: u16, y_prec: u16) -> TestResult {
+    let (x_prec, y_prec) = (PREC_OFFSET + x_prec as u32, PREC_OFFSET + y_prec as u32);
+    mixed(x, y, x_prec, y_prec, |x, y, mode| x.sub_ternary(y, mode))
+}
+#[quickcheck]
+fn mul_mixed(x: Vec<f64>, y: Vec<f64>, x_prec: u16, y_prec: u16) -> TestResult {
+    let (x_prec, y_prec) = (PREC_OFFSET + x_prec as u32, PREC_OFFSET + y_prec as u32);
+    mixed(x, y, x_prec, y_prec, |x, y, mode| x.mul_ternary(y, mode))
+}
+#[quickcheck]
+fn div_mixed(x: Vec<f64>, y: Vec<f64>, x_prec: u16, y_prec: u16) -> TestResult {
+    let (x_prec, y_prec) = (PREC_OFFSET + x_prec as u32, PREC_OFFSET + y_prec as u32);
+    mixed(x, y, x_prec, y_prec, |x, y, mode| x.div_ternary(y, mode))
+}
+
+#[quickcheck]
+fn add_to(x: Vec<f64>, y: Vec<f64>, prec: u16, out_prec: u16) -> TestResult {
+    let (prec, out_prec) = (PREC_OFFSET + prec as u32, 1 + out_prec as u32);
+    destination(x, y, prec, out_prec,
+                |x, y, p, mode| Float::add_to(x, y, p, mode),
+                |x, y, mode| x.add_ternary(y, mode))
+}
+#[quickcheck]
+fn sub_to(x: Vec<f64>, y: Vec<f64>, prec: u16, out_prec: u16) -> TestResult {
+    let (prec, out_prec) = (PREC_OFFSET + prec as u32, 1 + out_prec as u32);
+    destination(x, y, prec, out_prec,
+                |x, y, p, mode| Float::sub_to(x, y, p, mode),
+                |x, y, mode| x.sub_ternary(y, mode))
+}
+#[quickcheck]
+fn mul_to(x: Vec<f64>, y: Vec<f64>, prec: u16, out_prec: u16) -> TestResult {
+    let (prec, out_prec) = (PREC_OFFSET + prec as u32, 1 + out_prec as u32);
+    destination(x, y, prec, out_prec,
+                |x, y, p, mode| Float::mul_to(x, y, p, mode),
+                |x, y, mode| x.mul_ternary(y, mode))
+}
+#[quickcheck]
+fn div_to(x: Vec<f64>, y: Vec<f64>, prec: u16, out_prec: u16) -> TestResult {
+    let (prec, out_prec) = (PREC_OFFSET + prec as u32, 1 + out_prec as u32);
+    destination(x, y, prec, out_prec,
+                |x, y, p, mode| Float::div_to(x, y, p, mode),
+                |x, y, mode| x.div_ternary(y, mode))
+}
+#[quickcheck]
+fn sqrt_to(x: Vec<f64>, prec: u16, out_prec: u16) -> TestResult {
+    let (prec, out_prec) = (PREC_OFFSET + prec as u32, 1 + out_prec as u32);
+    destination(x, vec![], prec, out_prec,
+                |x, _, p, mode| Float::sqrt_to(&x.clone().abs(), p, mode),
+                |x, _, mode| x.abs().sqrt_ternary(mode))
+}
+
+#[test]
+fn sub_zero_sign() {
+    // an exact zero from subtraction is +0 when rounding to nearest,
+    // whichever way round the operands are passed.
+    let x = Float::from(1.5);
+    let zero = Float::zero(53);
+    for &(ref a, ref b) in &[(x.clone(), x.clone()), (zero.clone(), zero.clone())] {
+        for diff in vec![a.clone() - b.clone(), a.clone() - b, a - b.clone(), a - b,
+                         Float::sub_to(a, b, 53, RoundingMode::NearestEven)] {
+            assert!(diff == 0 && diff.sign() == Some(Sign::Pos), "{:?} - {:?}", a, b);
+        }
+    }
+}
@@ -32,6 +32,29 @@ macro_rules! tests {
                 assert_eq(f, x);
             }
 
+            #[quickcheck]
+            fn from_subnormal(signif: u64, neg: bool) {
+                let bits = $t::MANTISSA_DIGITS - 1;
+                let signif = signif & ((1 << bits) - 1);
+                let x = $t::MIN_POSITIVE * (signif as $t) / (1u64 << bits) as $t;
+                let (x, signif) = if neg { (-x, -(signif as i64)) } else { (x, signif as i64) };
+
+                let exact = Float::from(signif).with_precision($t::MANTISSA_DIGITS)
+                    .mul_exp2($t::MIN_EXP as i64 - $t::MANTISSA_DIGITS as i64);
+                let f = Float::from(x);
+                assert!(f == exact, "{:?} != {:?}", f, exact);
+            }
+
+            #[quickcheck]
+            fn mul_subnormal(x: $t, y: $t) {
+                // the exact product, rounded once by the conversion
+                let x = x * $t::MIN_POSITIVE;
+                let prec = 2 * $t::MANTISSA_DIGITS;
+                let f = Float::from(x).with_precision(prec);
+                let g = Float::from(y).with_precision(prec);
+                assert_eq(f * g, x * y);
+            }
+
             #[quickcheck]
             fn to_f32_prec(x: $t) {
                 let f = Float::from(x);
@@ -64,6 +87,17 @@ macro_rules! tests {
                 assert_eq(f + g, x + y);
             }
 
+            #[quickcheck]
+            fn mul_add(x: $t, y: $t, z: $t) {
+                // round in the range of $t, so that subnormal results
+                // are rounded once rather than twice by the conversion.
+                let (f, g, h) = (Float::from(x), Float::from(y), Float::from(z));
+                Float::set_exp_range($t::MIN_EXP as i64 - 1, $t::MAX_EXP as i64 - 1);
+                let fma = f.mul_add(&g, &h);
+                Float::reset_exp_range();
+                assert_eq(fma, x.mul_add(y, z));
+            }
+
             #[quickcheck]
             fn div(x: $t, y: $t) -> TestResult {
                 if y == 0.0 { return TestResult::discard() }
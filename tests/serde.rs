@@ -0,0 +1,72 @@
+#![cfg(feature = "serde")]
+
+extern crate float;
+extern crate serde_json;
+extern crate bincode;
+
+mod common;
+
+use common::identical;
+use float::Float;
+
+fn values() -> Vec<Float> {
+    vec![Float::zero(1), Float::neg_zero(7), Float::infinity(2), Float::neg_infinity(100),
+         Float::nan(53), -Float::nan(4), Float::from(1e300), Float::from(-0.1).with_precision(3),
+         Float::from(1).with_precision(1000).next_above(),
+         Float::from(-1).with_precision(200).mul_exp2(-(1 << 60)),
+         Float::max(64), Float::min_positive(64)]
+}
+
+#[test]
+fn json() {
+    let x = Float::from(-1.5).with_precision(4);
+    assert_eq!(serde_json::to_string(&x).unwrap(), "\"-1100 * 2^(0 - 4)\"");
+    assert_eq!(serde_json::to_string(&Float::neg_zero(3)).unwrap(), "\"-0.0 (prec 3)\"");
+    assert_eq!(serde_json::to_string(&Float::nan(3)).unwrap(), "\"NaN (prec 3)\"");
+    assert_eq!(serde_json::to_string(&-Float::nan(3)).unwrap(), "\"-NaN (prec 3)\"");
+
+    for x in values() {
+        let s = serde_json::to_string(&x).unwrap();
+        let y: Float = serde_json::from_str(&s).unwrap();
+        assert!(identical(&x, &y), "{}", s);
+    }
+
+    for s in &["\"1.5\"", "1.5", "[20, true, 0, [0, 0, 12]]", "\"+0.0\""] {
+        assert!(serde_json::from_str::<Float>(s).is_err(), "{}", s);
+    }
+}
+
+#[test]
+fn binary() {
+    let x = Float::from(-1.5).with_precision(20);
+    assert_eq!(bincode::serialize(&x).unwrap(),
+               [20, 0, 0, 0, // precision
+                1, // negative
+                0, 0, 0, 0, 0, 0, 0, 0, // exponent
+                3, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x0c]); // significand
+    let zero = bincode::serialize(&Float::neg_zero(3)).unwrap();
+    assert_eq!(zero, [3, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0x80, 0, 0, 0, 0, 0, 0, 0, 0]);
+    let nan = bincode::serialize(&Float::nan(3)).unwrap();
+    assert_eq!(nan, [3, 0, 0, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x7f,
+                     1, 0, 0, 0, 0, 0, 0, 0, 1]);
+
+    for x in values() {
+        let b = bincode::serialize(&x).unwrap();
+        let y: Float = bincode::deserialize(&b).unwrap();
+        assert!(identical(&x, &y), "{:?}", b);
+    }
+
+    let invalid: &[&[u8]] = &[
+        // zero precision
+        &[0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x80, 0, 0, 0, 0, 0, 0, 0, 0],
+        // the significand has too few bits
+        &[20, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0x0c],
+        // a zero with a significand
+        &[3, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0x80, 1, 0, 0, 0, 0, 0, 0, 0, 4],
+        // truncated
+        &[3, 0, 0, 0, 1, 0, 0, 0, 0],
+    ];
+    for b in invalid {
+        assert!(bincode::deserialize::<Float>(b).is_err(), "{:?}", b);
+    }
+}